@@ -0,0 +1,157 @@
+//! # Instruction Routing CU Benchmark
+//!
+//! Runs every instruction through two processors with the same handlers:
+//! `SinglePass`, routed by `#[pino_processor]`, which splits the
+//! discriminator off once and hands each handler its decoded arguments, and
+//! `DoubleParse`, which routes the way `ProgramInstruction` did before
+//! decoding became single-pass. There the instruction was decoded as a
+//! Borsh enum, its variant matched again to pick the handler, and the whole
+//! instruction data passed on to `parse_accounts`.
+//!
+//! The compute units each path consumes are logged in the benchmark output
+//! format, each label followed by a `sol_log_64` line holding the count:
+//!
+//! ```text
+//! Program log: Operation: instruction_routing
+//! Program log: CU Used: Pino (single-pass)
+//! Program log: 0x2a, 0x0, 0x0, 0x0, 0x0
+//! Program log: CU Used: Pino (double-parse)
+//! Program log: 0x3c, 0x0, 0x0, 0x0, 0x0
+//! ```
+
+use borsh::BorshDeserialize;
+use pino_core::{
+    entrypoint::{self, parse_accounts, ProgramInstruction},
+    pinocchio,
+    prelude::*,
+};
+
+// Declare the program ID
+declare_id!("Bench11111111111111111111111111111111111111");
+
+/// Greeting carried by the Greet instruction, borrowed straight from the
+/// instruction data
+#[derive(PinoData)]
+pub struct GreetingData<'a> {
+    /// The greeting text
+    pub greeting: &'a str,
+}
+
+/// Account validation shared by every instruction
+#[derive(Accounts)]
+pub struct Greet<'info> {
+    pub user: UncheckedAccount<'info>,
+}
+
+/// Handler shared by both processors, so that they differ only in routing
+pub fn greet_user(_ctx: Context<Greet>, greeting: &str) -> ProgramResult {
+    if greeting.is_empty() {
+        return Err(PinoError::InvalidInstructionData.into());
+    }
+    Ok(())
+}
+
+/// Single-pass routing, generated by `#[pino_processor]`
+pub struct SinglePass;
+
+#[pino_processor]
+impl SinglePass {
+    /// Greet with a fixed greeting, taking no arguments
+    #[instruction(0)]
+    pub fn say_hello(ctx: Context<Greet>) -> ProgramResult {
+        greet_user(ctx, "hello")
+    }
+
+    /// Greet with the greeting from the instruction data
+    #[instruction(1)]
+    pub fn greet(ctx: Context<Greet>, data: GreetingData<'_>) -> ProgramResult {
+        greet_user(ctx, data.greeting)
+    }
+}
+
+/// Instructions of the double-parse path. Index discriminators give
+/// `SinglePass` the same wire format.
+#[derive(BorshDeserialize)]
+pub enum DoubleParseInstruction {
+    /// Greet with a fixed greeting
+    SayHello,
+    /// Greet with the given greeting
+    Greet { greeting: String },
+}
+
+/// Double-parse routing, as `ProgramInstruction` implementations were
+/// written before decoding became single-pass
+pub struct DoubleParse;
+
+impl ProgramInstruction for DoubleParse {
+    type Instruction<'a> = DoubleParseInstruction;
+
+    fn decode(instruction_data: &[u8]) -> Result<(DoubleParseInstruction, &[u8])> {
+        let instruction =
+            <DoubleParseInstruction as InstructionData>::try_from_slice(instruction_data)?;
+        // The discriminator was not split off, so `process` sees it again
+        Ok((instruction, instruction_data))
+    }
+
+    fn process<'a>(
+        instruction: DoubleParseInstruction,
+        program_id: &'a Pubkey,
+        accounts: RemainingAccounts<'a>,
+        instruction_data: &'a [u8],
+    ) -> ProgramResult {
+        let mut slots = Default::default();
+        let mut parsed =
+            parse_accounts::<Greet>(program_id, accounts, &mut slots, instruction_data)?;
+        let ctx = parsed.context(program_id, instruction_data);
+
+        match instruction {
+            DoubleParseInstruction::SayHello => greet_user(ctx, "hello")?,
+            DoubleParseInstruction::Greet { greeting } => greet_user(ctx, &greeting)?,
+        }
+        Accounts::exit(&parsed.accounts, program_id)
+    }
+}
+
+/// Runs the instruction through both processors and logs the compute units
+/// each one consumed.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let single_pass = consumed(|| {
+        entrypoint::process_instruction::<SinglePass>(program_id, accounts, instruction_data)
+    })?;
+    let double_parse = consumed(|| {
+        entrypoint::process_instruction::<DoubleParse>(program_id, accounts, instruction_data)
+    })?;
+
+    logging::log_message("Operation: instruction_routing");
+    logging::log_number("CU Used: Pino (single-pass)", single_pass);
+    logging::log_number("CU Used: Pino (double-parse)", double_parse);
+    Ok(())
+}
+
+/// Returns the compute units `run` consumed. Both measurements include the
+/// cost of one `sol_remaining_compute_units` call, so it cancels out of
+/// their difference.
+fn consumed(run: impl FnOnce() -> ProgramResult) -> core::result::Result<u64, ProgramError> {
+    let before = remaining_compute_units();
+    run()?;
+    Ok(before.saturating_sub(remaining_compute_units()))
+}
+
+/// Returns the compute units left in the transaction, or 0 off-chain.
+fn remaining_compute_units() -> u64 {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        pinocchio::syscalls::sol_remaining_compute_units()
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    0
+}
+
+program_entrypoint!(process_instruction);
+default_allocator!();
+default_panic_handler!();
@@ -0,0 +1,71 @@
+//! Compares the compute units of single-pass and double-parse instruction
+//! routing. Run with `cargo test-sbf --features test-bpf -- --nocapture`,
+//! which builds the program before loading it into the test validator.
+
+#![cfg(feature = "test-bpf")]
+
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+
+/// Reads the count logged by `sol_log_64` on the line after `label`.
+fn logged_cu(logs: &[String], label: &str) -> u64 {
+    let line = logs
+        .iter()
+        .skip_while(|line| !line.ends_with(label))
+        .nth(1)
+        .unwrap_or_else(|| panic!("no `{label}` in the program logs"));
+    let count = line
+        .trim_start_matches("Program log: 0x")
+        .split(',')
+        .next()
+        .unwrap();
+    u64::from_str_radix(count, 16).unwrap()
+}
+
+#[tokio::test]
+async fn single_pass_routing_consumes_fewer_cu_than_double_parse() {
+    let program_id = Pubkey::new_from_array(cu_consumption::ID);
+    let mut program_test = ProgramTest::new("cu_consumption", program_id, None);
+    program_test.prefer_bpf(true);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let mut greet = vec![1];
+    greet.extend_from_slice(&5u32.to_le_bytes());
+    greet.extend_from_slice(b"hello");
+
+    for (operation, data) in [("say_hello", vec![0]), ("greet", greet)] {
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![AccountMeta::new_readonly(payer.pubkey(), false)],
+        );
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        result.result.unwrap();
+        let logs = result.metadata.unwrap().log_messages;
+
+        let single_pass = logged_cu(&logs, "CU Used: Pino (single-pass)");
+        let double_parse = logged_cu(&logs, "CU Used: Pino (double-parse)");
+        println!(
+            "Operation: instruction_routing ({operation})\n\
+             Single-pass CU Used: {single_pass}\n\
+             Double-parse CU Used: {double_parse}\n\
+             Improvement: {} CU\n",
+            double_parse.saturating_sub(single_pass),
+        );
+        assert!(single_pass <= double_parse);
+    }
+}
@@ -0,0 +1,134 @@
+//! IDL command implementation
+
+use anyhow::{Context, Result};
+use colored::*;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Prefix of the lines carrying IDL fragments, printed by the `idl-build`
+/// tests that the Pino macros generate.
+const IDL_MARKER: &str = "__pino_idl__:";
+
+/// Version of the Anchor IDL specification produced.
+const IDL_SPEC: &str = "0.1.0";
+
+/// Extracts the IDL of the current project into Anchor-compatible JSON.
+pub async fn build(out: Option<&str>) -> Result<()> {
+    println!("{} Building IDL...", "📜".blue());
+
+    let (name, version) = read_package()?;
+
+    let output = Command::new("cargo")
+        .args([
+            "test",
+            "--features",
+            "idl-build",
+            "__pino_idl_print",
+            "--",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        println!("{} IDL build failed:", "❌".red());
+        println!("{}", stderr);
+        anyhow::bail!("IDL build failed");
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let idl = assemble(&name, &version, &stdout)?;
+
+    let path = match out {
+        Some(out) => PathBuf::from(out),
+        None => PathBuf::from("target/idl").join(format!("{}.json", name.replace('-', "_"))),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&idl)?)
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+
+    println!("{} IDL written to {}", "✅".green(), path.display());
+    Ok(())
+}
+
+/// Reads the package name and version from `Cargo.toml`.
+fn read_package() -> Result<(String, String)> {
+    let manifest = fs::read_to_string("Cargo.toml").context("No Cargo.toml in the current directory")?;
+    let manifest: toml::Value = toml::from_str(&manifest).context("Failed to parse Cargo.toml")?;
+
+    let package = manifest
+        .get("package")
+        .context("Cargo.toml has no [package] section")?;
+    let name = package
+        .get("name")
+        .and_then(toml::Value::as_str)
+        .context("Cargo.toml has no package name")?;
+    let version = package
+        .get("version")
+        .and_then(toml::Value::as_str)
+        .unwrap_or("0.1.0");
+
+    Ok((name.to_string(), version.to_string()))
+}
+
+/// Assembles the IDL from the fragments found in the test output.
+fn assemble(name: &str, version: &str, output: &str) -> Result<Value> {
+    let mut address = None;
+    let mut instructions = Vec::new();
+    let mut accounts = Vec::new();
+    let mut types: Vec<Value> = Vec::new();
+    let mut errors = Vec::new();
+
+    for line in output.lines() {
+        let Some(fragment) = line.trim().strip_prefix(IDL_MARKER) else {
+            continue;
+        };
+        let (kind, json) = fragment
+            .split_once(' ')
+            .with_context(|| format!("Malformed IDL fragment: {}", fragment))?;
+        let value: Value = serde_json::from_str(json)
+            .with_context(|| format!("Malformed IDL fragment: {}", fragment))?;
+
+        match kind {
+            "address" => address = Some(value),
+            "instruction" => instructions.push(value),
+            "account" => accounts.push(value),
+            // A type may be reported by several items; keep the first.
+            "type" => {
+                if !types.iter().any(|known| known["name"] == value["name"]) {
+                    types.push(value);
+                }
+            }
+            "error" => errors.push(value),
+            _ => println!("{} Ignoring unknown IDL fragment '{}'", "⚠️".yellow(), kind),
+        }
+    }
+
+    if instructions.is_empty() {
+        anyhow::bail!(
+            "No instructions found; is the `idl-build` feature forwarded to pino-core?"
+        );
+    }
+    let address = address.unwrap_or_else(|| {
+        println!("{} No declare_id! found, leaving the address empty", "⚠️".yellow());
+        Value::String(String::new())
+    });
+
+    let mut idl = Map::new();
+    idl.insert("address".into(), address);
+    idl.insert(
+        "metadata".into(),
+        json!({ "name": name.replace('-', "_"), "version": version, "spec": IDL_SPEC }),
+    );
+    idl.insert("instructions".into(), Value::Array(instructions));
+    idl.insert("accounts".into(), Value::Array(accounts));
+    idl.insert("errors".into(), Value::Array(errors));
+    idl.insert("types".into(), Value::Array(types));
+
+    Ok(Value::Object(idl))
+}
@@ -7,4 +7,5 @@ pub mod test;
 pub mod dev;
 pub mod deploy;
 pub mod analyze;
-pub mod migrate; 
\ No newline at end of file
+pub mod migrate;
+pub mod idl; 
\ No newline at end of file
@@ -52,6 +52,12 @@ bytemuck = "1.20"
 
 [lib]
 crate-type = ["cdylib", "lib"]
+
+[features]
+idl-build = ["pino-core/idl-build"]
+debug-errors = ["pino-core/debug-errors"]
+strip-error-logs = ["pino-core/strip-error-logs"]
+strip-panic-logs = ["pino-core/strip-panic-logs"]
 "#, project_path.file_name().unwrap().to_string_lossy());
     
     fs::write(project_path.join("Cargo.toml"), cargo_toml_content)?;
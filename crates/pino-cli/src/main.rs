@@ -73,6 +73,21 @@ pub enum Commands {
         /// Source directory
         source: String,
     },
+    /// Work with the program IDL
+    Idl {
+        #[command(subcommand)]
+        command: IdlCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IdlCommands {
+    /// Extract the IDL into Anchor-compatible JSON
+    Build {
+        /// Output file (defaults to target/idl/<program>.json)
+        #[arg(short, long)]
+        out: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -109,6 +124,11 @@ async fn main() -> Result<()> {
         Commands::Migrate { from, source } => {
             commands::migrate::execute(&from, &source).await?;
         }
+        Commands::Idl { command } => match command {
+            IdlCommands::Build { out } => {
+                commands::idl::build(out.as_deref()).await?;
+            }
+        },
     }
 
     Ok(())
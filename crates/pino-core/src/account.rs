@@ -12,6 +12,53 @@ use pinocchio::{
 use bytemuck::Pod;
 use crate::error::{PinoError, require_check};
 
+/// Size of the discriminator that prefixes the data of `#[pino_account]` types.
+pub const DISCRIMINATOR_SIZE: usize = 8;
+
+/// Account data types declared with `#[pino_account]`.
+///
+/// The account data starts with [`AccountData::DISCRIMINATOR`], followed by
+/// the `Pod` representation of the type.
+pub trait AccountData: Pod {
+    /// Identifies the type; the first 8 bytes of `sha256("account:<Name>")`.
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_SIZE];
+
+    /// Size of the account data, discriminator included.
+    const LEN: usize = DISCRIMINATOR_SIZE + core::mem::size_of::<Self>();
+
+    /// Returns the program that owns accounts of this type: the declaring
+    /// crate's `ID`, unless `#[pino_account(owner = ...)]` names another.
+    fn owner() -> Pubkey;
+}
+
+/// Verifies that `info` is owned by the program of `T` and holds a `T`.
+fn check_account_data<T: AccountData>(info: &AccountInfo) -> Result<(), PinoError> {
+    require_check(info.is_owned_by(&T::owner()), PinoError::InvalidAccountOwner)?;
+
+    let data = unsafe { info.borrow_data_unchecked() };
+    require_check(data.len() >= T::LEN, PinoError::AccountDataTooSmall)?;
+    require_check(
+        data[..DISCRIMINATOR_SIZE] == T::DISCRIMINATOR,
+        PinoError::AccountDiscriminatorMismatch,
+    )
+}
+
+/// Writes the discriminator of `T` into a freshly created account.
+fn write_discriminator<T: AccountData>(info: &AccountInfo) -> Result<(), PinoError> {
+    require_check(info.is_writable(), PinoError::AccountNotMutable)?;
+    require_check(info.is_owned_by(&T::owner()), PinoError::InvalidAccountOwner)?;
+
+    let data = unsafe { info.borrow_mut_data_unchecked() };
+    require_check(data.len() >= T::LEN, PinoError::AccountDataTooSmall)?;
+    require_check(
+        data[..DISCRIMINATOR_SIZE] == [0; DISCRIMINATOR_SIZE],
+        PinoError::AccountAlreadyInitialized,
+    )?;
+
+    data[..DISCRIMINATOR_SIZE].copy_from_slice(&T::DISCRIMINATOR);
+    Ok(())
+}
+
 /// A zero-copy account wrapper that provides type-safe access to account data.
 ///
 /// This is the primary account type in Pino, providing safe access to account
@@ -21,6 +68,16 @@ pub struct Account<'info, T> {
     _phantom: PhantomData<T>,
 }
 
+// The wrappers below only hold a reference, so they are `Copy` regardless
+// of `T`; derived impls would needlessly require `T: Copy`.
+impl<'info, T> Clone for Account<'info, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'info, T> Copy for Account<'info, T> {}
+
 impl<'info, T> Account<'info, T> {
     /// Creates a new Account wrapper around an AccountInfo.
     ///
@@ -35,27 +92,13 @@ impl<'info, T> Account<'info, T> {
         }
     }
 
-    /// Creates a new Account wrapper with validation.
-    pub fn new(info: &'info AccountInfo) -> Result<Self, PinoError> {
-        // Basic validation
-        require_check(
-            info.data_len() >= core::mem::size_of::<T>(),
-            PinoError::AccountDataTooSmall
-        )?;
-
-        Ok(Self {
-            info,
-            _phantom: PhantomData,
-        })
-    }
-
     /// Returns the underlying AccountInfo.
     pub fn info(&self) -> &'info AccountInfo {
         self.info
     }
 
     /// Returns the account's public key.
-    pub fn key(&self) -> &Pubkey {
+    pub fn key(&self) -> &'info Pubkey {
         self.info.key()
     }
 
@@ -85,47 +128,66 @@ impl<'info, T> Account<'info, T> {
     }
 }
 
-impl<'info, T: Pod> Account<'info, T> {
+impl<'info, T: AccountData> Account<'info, T> {
+    /// Creates a new Account wrapper with validation.
+    ///
+    /// The account must be owned by the program of `T` and start with the
+    /// discriminator of `T`.
+    pub fn new(info: &'info AccountInfo) -> Result<Self, PinoError> {
+        check_account_data::<T>(info)?;
+
+        Ok(Self {
+            info,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Writes the discriminator of `T` into a freshly created account and
+    /// wraps it.
+    ///
+    /// Fails if the account already holds a discriminator.
+    pub fn try_initialize(info: &'info AccountInfo) -> Result<Self, PinoError> {
+        write_discriminator::<T>(info)?;
+
+        Ok(Self {
+            info,
+            _phantom: PhantomData,
+        })
+    }
+
     /// Loads the account data as a reference to T.
     ///
     /// This provides zero-copy access to the account data.
     pub fn load(&self) -> Result<&T, PinoError> {
-        let data = unsafe { self.info.borrow_data_unchecked() };
-        
-        require_check(
-            data.len() >= core::mem::size_of::<T>(),
-            PinoError::AccountDataTooSmall
-        )?;
+        check_account_data::<T>(self.info)?;
 
-        Ok(bytemuck::from_bytes(&data[..core::mem::size_of::<T>()]))
+        let data = unsafe { self.info.borrow_data_unchecked() };
+        Ok(bytemuck::from_bytes(&data[DISCRIMINATOR_SIZE..T::LEN]))
     }
 
     /// Loads the account data as a mutable reference to T.
     pub fn load_mut(&self) -> Result<&mut T, PinoError> {
         require_check(self.is_writable(), PinoError::AccountNotMutable)?;
+        check_account_data::<T>(self.info)?;
 
         let data = unsafe { self.info.borrow_mut_data_unchecked() };
-        
-        require_check(
-            data.len() >= core::mem::size_of::<T>(),
-            PinoError::AccountDataTooSmall
-        )?;
-
-        Ok(bytemuck::from_bytes_mut(&mut data[..core::mem::size_of::<T>()]))
+        Ok(bytemuck::from_bytes_mut(&mut data[DISCRIMINATOR_SIZE..T::LEN]))
     }
 
     /// Initializes the account data with the given value.
+    ///
+    /// Writes the discriminator as well, so it can be used on freshly
+    /// created accounts.
     pub fn init(&self, value: T) -> Result<(), PinoError> {
         require_check(self.is_writable(), PinoError::AccountNotMutable)?;
+        require_check(self.info.is_owned_by(&T::owner()), PinoError::InvalidAccountOwner)?;
         
         let data = unsafe { self.info.borrow_mut_data_unchecked() };
         
-        require_check(
-            data.len() >= core::mem::size_of::<T>(),
-            PinoError::AccountDataTooSmall
-        )?;
+        require_check(data.len() >= T::LEN, PinoError::AccountDataTooSmall)?;
 
-        let account_data = bytemuck::from_bytes_mut(&mut data[..core::mem::size_of::<T>()]);
+        data[..DISCRIMINATOR_SIZE].copy_from_slice(&T::DISCRIMINATOR);
+        let account_data = bytemuck::from_bytes_mut(&mut data[DISCRIMINATOR_SIZE..T::LEN]);
         *account_data = value;
         
         Ok(())
@@ -133,6 +195,7 @@ impl<'info, T: Pod> Account<'info, T> {
 }
 
 /// A signer account wrapper.
+#[derive(Clone, Copy)]
 pub struct Signer<'info> {
     info: &'info AccountInfo,
 }
@@ -151,7 +214,7 @@ impl<'info> Signer<'info> {
     }
 
     /// Returns the signer's public key.
-    pub fn key(&self) -> &Pubkey {
+    pub fn key(&self) -> &'info Pubkey {
         self.info.key()
     }
 }
@@ -162,6 +225,14 @@ pub struct Program<'info, T> {
     _phantom: PhantomData<T>,
 }
 
+impl<'info, T> Clone for Program<'info, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'info, T> Copy for Program<'info, T> {}
+
 impl<'info, T> Program<'info, T> {
     /// Creates a new Program wrapper.
     pub fn new(info: &'info AccountInfo, expected_id: &Pubkey) -> Result<Self, PinoError> {
@@ -180,15 +251,23 @@ impl<'info, T> Program<'info, T> {
     }
 
     /// Returns the program's public key.
-    pub fn key(&self) -> &Pubkey {
+    pub fn key(&self) -> &'info Pubkey {
         self.info.key()
     }
 }
 
+impl<'info, T: crate::program::Program> Program<'info, T> {
+    /// Creates a new Program wrapper, validating against the ID of `T`.
+    pub fn try_from_info(info: &'info AccountInfo) -> Result<Self, PinoError> {
+        Self::new(info, &T::id())
+    }
+}
+
 /// An unchecked account wrapper for maximum flexibility.
 ///
 /// Use this when you need direct access to AccountInfo without validation.
 /// Be careful - this bypasses all safety checks.
+#[derive(Clone, Copy)]
 pub struct UncheckedAccount<'info> {
     info: &'info AccountInfo,
 }
@@ -205,7 +284,7 @@ impl<'info> UncheckedAccount<'info> {
     }
 
     /// Converts to a typed Account with validation.
-    pub fn to_account<T>(&self) -> Result<Account<'info, T>, PinoError> {
+    pub fn to_account<T: AccountData>(&self) -> Result<Account<'info, T>, PinoError> {
         Account::new(self.info)
     }
 
@@ -229,13 +308,32 @@ pub struct AccountLoader<'info, T> {
     _phantom: PhantomData<T>,
 }
 
-impl<'info, T: Pod> AccountLoader<'info, T> {
+impl<'info, T> Clone for AccountLoader<'info, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'info, T> Copy for AccountLoader<'info, T> {}
+
+impl<'info, T: AccountData> AccountLoader<'info, T> {
     /// Creates a new AccountLoader.
+    ///
+    /// The account must be owned by the program of `T` and start with the
+    /// discriminator of `T`.
     pub fn new(info: &'info AccountInfo) -> Result<Self, PinoError> {
-        require_check(
-            info.data_len() >= core::mem::size_of::<T>(),
-            PinoError::AccountDataTooSmall
-        )?;
+        check_account_data::<T>(info)?;
+
+        Ok(Self {
+            info,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Writes the discriminator of `T` into a freshly created account and
+    /// wraps it.
+    pub fn try_initialize(info: &'info AccountInfo) -> Result<Self, PinoError> {
+        write_discriminator::<T>(info)?;
 
         Ok(Self {
             info,
@@ -245,11 +343,13 @@ impl<'info, T: Pod> AccountLoader<'info, T> {
 
     /// Loads the account data as an immutable reference.
     pub fn load(&self) -> Result<Ref<T>, PinoError> {
+        check_account_data::<T>(self.info)?;
+
         let data = self.info.try_borrow_data()
             .map_err(|_| PinoError::AccountBorrowFailed)?;
         
         let account_ref = Ref::map(data, |data| {
-            bytemuck::from_bytes(&data[..core::mem::size_of::<T>()])
+            bytemuck::from_bytes(&data[DISCRIMINATOR_SIZE..T::LEN])
         });
         
         Ok(account_ref)
@@ -258,12 +358,13 @@ impl<'info, T: Pod> AccountLoader<'info, T> {
     /// Loads the account data as a mutable reference.
     pub fn load_mut(&self) -> Result<RefMut<T>, PinoError> {
         require_check(self.info.is_writable(), PinoError::AccountNotMutable)?;
+        check_account_data::<T>(self.info)?;
 
         let data = self.info.try_borrow_mut_data()
             .map_err(|_| PinoError::AccountBorrowFailed)?;
         
         let account_ref = RefMut::map(data, |data| {
-            bytemuck::from_bytes_mut(&mut data[..core::mem::size_of::<T>()])
+            bytemuck::from_bytes_mut(&mut data[DISCRIMINATOR_SIZE..T::LEN])
         });
         
         Ok(account_ref)
@@ -275,6 +376,48 @@ impl<'info, T: Pod> AccountLoader<'info, T> {
     }
 }
 
+/// Account wrappers that can hand out their `AccountInfo`.
+pub trait ToAccountInfo<'info> {
+    /// Returns the underlying AccountInfo.
+    fn to_account_info(&self) -> &'info AccountInfo;
+}
+
+impl<'info> ToAccountInfo<'info> for &'info AccountInfo {
+    fn to_account_info(&self) -> &'info AccountInfo {
+        self
+    }
+}
+
+impl<'info, T> ToAccountInfo<'info> for Account<'info, T> {
+    fn to_account_info(&self) -> &'info AccountInfo {
+        self.info
+    }
+}
+
+impl<'info> ToAccountInfo<'info> for Signer<'info> {
+    fn to_account_info(&self) -> &'info AccountInfo {
+        self.info
+    }
+}
+
+impl<'info, T> ToAccountInfo<'info> for Program<'info, T> {
+    fn to_account_info(&self) -> &'info AccountInfo {
+        self.info
+    }
+}
+
+impl<'info> ToAccountInfo<'info> for UncheckedAccount<'info> {
+    fn to_account_info(&self) -> &'info AccountInfo {
+        self.info
+    }
+}
+
+impl<'info, T> ToAccountInfo<'info> for AccountLoader<'info, T> {
+    fn to_account_info(&self) -> &'info AccountInfo {
+        self.info
+    }
+}
+
 /// System program marker type.
 pub struct System;
 
@@ -284,20 +427,35 @@ pub struct Token;
 /// Associated token program marker type.
 pub struct AssociatedToken;
 
+impl crate::program::Program for System {
+    fn id() -> Pubkey {
+        program_ids::SYSTEM_PROGRAM_ID
+    }
+}
+
+impl crate::program::Program for Token {
+    fn id() -> Pubkey {
+        program_ids::TOKEN_PROGRAM_ID
+    }
+}
+
+impl crate::program::Program for AssociatedToken {
+    fn id() -> Pubkey {
+        program_ids::ASSOCIATED_TOKEN_PROGRAM_ID
+    }
+}
+
 /// Common program IDs
 pub mod program_ids {
     use super::Pubkey;
 
     /// System program ID: 11111111111111111111111111111111
-    pub const SYSTEM_PROGRAM_ID: Pubkey = [
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
-    ];
+    pub const SYSTEM_PROGRAM_ID: Pubkey = [0; 32];
 
     /// SPL Token program ID: TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA
     pub const TOKEN_PROGRAM_ID: Pubkey = [
-        6, 155, 136, 87, 254, 171, 129, 132, 251, 104, 127, 99, 70, 24, 192, 53,
-        218, 196, 57, 220, 26, 235, 59, 85, 152, 160, 240, 0, 0, 0, 0, 1,
+        6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172,
+        28, 180, 133, 237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
     ];
 
     /// Associated Token program ID: ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL
@@ -305,4 +463,48 @@ pub mod program_ids {
         140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131,
         11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
     ];
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::program_ids::*;
+    use super::Pubkey;
+
+    /// Decodes a base58 address, as printed by explorers and the CLI.
+    fn decode(address: &str) -> Pubkey {
+        const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+        let mut key = [0u8; 32];
+        for c in address.bytes() {
+            let mut carry = ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+            for byte in key.iter_mut().rev() {
+                carry += *byte as u32 * 58;
+                *byte = carry as u8;
+                carry >>= 8;
+            }
+            assert_eq!(carry, 0, "address does not fit in 32 bytes");
+        }
+        key
+    }
+
+    #[test]
+    fn system_program_id() {
+        assert_eq!(SYSTEM_PROGRAM_ID, decode("11111111111111111111111111111111"));
+    }
+
+    #[test]
+    fn token_program_id() {
+        assert_eq!(
+            TOKEN_PROGRAM_ID,
+            decode("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+        );
+    }
+
+    #[test]
+    fn associated_token_program_id() {
+        assert_eq!(
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            decode("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")
+        );
+    }
+}
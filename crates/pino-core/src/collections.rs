@@ -50,7 +50,7 @@ impl<T, const N: usize> StackVec<T, N> {
     /// Pushes an element to the end of the vector.
     pub fn push(&mut self, value: T) -> Result<(), PinoError> {
         if self.len >= N {
-            return Err(PinoError::Custom(0x1001)); // StackVec capacity exceeded
+            return Err(PinoError::StackVecFull);
         }
 
         self.data[self.len] = MaybeUninit::new(value);
@@ -99,6 +99,53 @@ impl<T, const N: usize> StackVec<T, N> {
     }
 }
 
+impl<T: Clone, const N: usize> StackVec<T, N> {
+    /// Appends clones of `values`, or nothing if they do not all fit.
+    pub fn extend_from_slice(&mut self, values: &[T]) -> Result<(), PinoError> {
+        if values.len() > N - self.len {
+            return Err(PinoError::StackVecFull);
+        }
+
+        for value in values {
+            self.data[self.len] = MaybeUninit::new(value.clone());
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Grows or shrinks the vector to `new_len`, filling new slots with
+    /// `value`.
+    pub fn resize(&mut self, new_len: usize, value: T) -> Result<(), PinoError> {
+        if new_len > N {
+            return Err(PinoError::StackVecFull);
+        }
+
+        while self.len > new_len {
+            self.pop();
+        }
+        while self.len < new_len {
+            self.data[self.len] = MaybeUninit::new(value.clone());
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Lets Borsh serialize into a byte vector without allocating. Bytes that do
+/// not fit are not written, which fails the serialization.
+impl<const N: usize> borsh::io::Write for StackVec<u8, N> {
+    fn write(&mut self, buf: &[u8]) -> borsh::io::Result<usize> {
+        let len = buf.len().min(N - self.len);
+        // Cannot fail: `len` bytes fit.
+        let _ = self.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> borsh::io::Result<()> {
+        Ok(())
+    }
+}
+
 impl<T, const N: usize> Drop for StackVec<T, N> {
     fn drop(&mut self) {
         self.clear();
@@ -173,7 +220,7 @@ impl<K: PartialEq, V, const N: usize> StackMap<K, V, N> {
 
         // Key doesn't exist, add new entry
         if self.len >= N {
-            return Err(PinoError::Custom(0x1002)); // StackMap is full
+            return Err(PinoError::StackMapFull);
         }
 
         self.data[self.len] = MaybeUninit::new((key, value));
@@ -291,7 +338,7 @@ impl<const N: usize> StackString<N> {
     pub fn push_str(&mut self, s: &str) -> Result<(), PinoError> {
         let bytes = s.as_bytes();
         if self.len + bytes.len() > N {
-            return Err(PinoError::Custom(0x1003)); // StackString capacity exceeded
+            return Err(PinoError::StackStringFull);
         }
 
         self.data[self.len..self.len + bytes.len()].copy_from_slice(bytes);
@@ -311,7 +358,7 @@ impl<const N: usize> StackString<N> {
         // Simple hex encoding for pubkey
         for byte in pubkey {
             if self.len + 2 > N {
-                return Err(PinoError::Custom(0x1003));
+                return Err(PinoError::StackStringFull);
             }
             let hex_chars = [
                 b"0123456789abcdef"[(*byte >> 4) as usize],
@@ -324,6 +371,24 @@ impl<const N: usize> StackString<N> {
         Ok(())
     }
 
+    /// Appends the decimal representation of a number to this string.
+    pub fn push_number(&mut self, value: u64) -> Result<(), PinoError> {
+        let mut digits = [0u8; 20];
+        let mut start = digits.len();
+        let mut value = value;
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+
+        // Only ASCII digits were written.
+        self.push_str(unsafe { core::str::from_utf8_unchecked(&digits[start..]) })
+    }
+
     /// Returns the string as a &str.
     pub fn as_str(&self) -> &str {
         unsafe { core::str::from_utf8_unchecked(&self.data[..self.len]) }
@@ -3,49 +3,56 @@
 //! This module provides the Context type that organizes accounts and instruction
 //! data in a type-safe manner while maintaining Pinocchio's zero-copy efficiency.
 
-extern crate alloc;
-
-use core::marker::PhantomData;
-use alloc::vec::Vec;
+use core::{marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
 use pinocchio::{
     account_info::AccountInfo,
+    entrypoint::{InstructionContext, MaybeAccount},
+    instruction::{AccountMeta, Instruction, Signer},
+    program_error::ProgramError,
     pubkey::Pubkey,
     ProgramResult,
 };
-use crate::error::PinoError;
+use crate::{
+    account::{Program, ToAccountInfo},
+    collections::StackVec,
+    error::PinoError,
+};
 
 /// Context provides structured access to accounts and instruction data.
 ///
 /// This is the primary way to access accounts in Pino instruction handlers.
 /// It provides type-safe access to validated accounts while maintaining
 /// zero-copy efficiency.
-pub struct Context<'info, T> {
+pub struct Context<'a, 'info, T: Bumps> {
     /// The program ID that owns this instruction
     pub program_id: &'info Pubkey,
     /// The structured accounts
-    pub accounts: T,
+    pub accounts: &'a mut T,
     /// The remaining accounts not captured in the accounts struct
-    pub remaining_accounts: &'info [AccountInfo],
+    pub remaining_accounts: RemainingAccounts<'info>,
     /// The instruction data
     pub instruction_data: &'info [u8],
-    /// Bump seeds for PDAs (if any)
-    pub bumps: BumpSeeds,
+    /// Bump seeds of the PDA accounts, by field name
+    pub bumps: T::Bumps,
+    /// Signer seeds of the PDA accounts
+    pub signer_seeds: T::SignerSeeds<'info>,
 }
 
-impl<'info, T> Context<'info, T> {
+impl<'a, 'info, T: Bumps> Context<'a, 'info, T> {
     /// Creates a new Context.
     pub fn new(
         program_id: &'info Pubkey,
-        accounts: T,
+        accounts: &'a mut T,
         remaining_accounts: &'info [AccountInfo],
         instruction_data: &'info [u8],
     ) -> Self {
         Self {
             program_id,
             accounts,
-            remaining_accounts,
+            remaining_accounts: RemainingAccounts::Parsed(remaining_accounts),
             instruction_data,
-            bumps: BumpSeeds::new(),
+            bumps: T::Bumps::default(),
+            signer_seeds: Default::default(),
         }
     }
 
@@ -60,52 +67,329 @@ impl<'info, T> Context<'info, T> {
     }
 
     /// Returns the remaining accounts.
-    pub fn remaining_accounts(&self) -> &[AccountInfo] {
-        self.remaining_accounts
+    pub fn remaining_accounts(&mut self) -> &mut RemainingAccounts<'info> {
+        &mut self.remaining_accounts
+    }
+
+    /// Returns the signer of a PDA account validated with `seeds`, ready for
+    /// `invoke_signed`.
+    pub fn signer_seeds_for(
+        &self,
+        account: &impl ToAccountInfo<'info>,
+    ) -> Result<Signer<'info, '_>, PinoError> {
+        self.signer_seeds
+            .signer_for(account.to_account_info().key())
+            .ok_or(PinoError::NoSignerSeeds)
+    }
+}
+
+/// Accounts of an instruction that have not been taken yet.
+///
+/// The standard entrypoint parses every account up front; the lazy
+/// entrypoint leaves them in the program input and reads each one as it is
+/// taken. [`Accounts::try_accounts`] takes its accounts from the front and
+/// leaves the rest for the handler.
+pub enum RemainingAccounts<'info> {
+    /// Accounts parsed by the entrypoint.
+    Parsed(&'info [AccountInfo]),
+    /// Accounts still in the program input.
+    Lazy(LazyAccounts<'info>),
+}
+
+impl<'info> RemainingAccounts<'info> {
+    /// Takes the next account.
+    pub fn take(&mut self) -> Result<&'info AccountInfo, PinoError> {
+        match self {
+            RemainingAccounts::Parsed(accounts) => {
+                let (first, rest) = accounts
+                    .split_first()
+                    .ok_or(PinoError::NotEnoughAccountKeys)?;
+                *accounts = rest;
+                Ok(first)
+            }
+            RemainingAccounts::Lazy(accounts) => accounts.take(),
+        }
+    }
+
+    /// Returns the number of accounts left.
+    pub fn len(&self) -> usize {
+        match self {
+            RemainingAccounts::Parsed(accounts) => accounts.len(),
+            RemainingAccounts::Lazy(accounts) => accounts.remaining() as usize,
+        }
+    }
+
+    /// Returns true if no accounts are left.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stores the accounts taken lazily from now on in `slots`.
+    pub(crate) fn with_slots(self, slots: &'info mut [MaybeUninit<AccountInfo>]) -> Self {
+        match self {
+            RemainingAccounts::Lazy(accounts) => RemainingAccounts::Lazy(accounts.with_slots(slots)),
+            parsed => parsed,
+        }
+    }
+}
+
+/// Yields the accounts left, as the program input lists them.
+///
+/// Parsed accounts are always whole. A lazy entry that repeats an account
+/// taken by the Accounts struct is resolved to it; one that repeats an
+/// earlier remaining account is yielded as `MaybeAccount::Duplicated` with
+/// the index of that account in the instruction.
+impl Iterator for RemainingAccounts<'_> {
+    type Item = MaybeAccount;
+
+    fn next(&mut self) -> Option<MaybeAccount> {
+        match self {
+            RemainingAccounts::Parsed(accounts) => {
+                let (first, rest) = accounts.split_first()?;
+                *accounts = rest;
+                Some(MaybeAccount::Account(first.clone()))
+            }
+            RemainingAccounts::Lazy(accounts) => accounts.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl ExactSizeIterator for RemainingAccounts<'_> {}
+
+/// Reads the accounts of an instruction from the program input one at a
+/// time.
+///
+/// Accounts taken for an Accounts struct are kept in its [`AccountSlots`],
+/// which later duplicates are resolved against by index.
+pub struct LazyAccounts<'info> {
+    context: InstructionContext,
+    /// Entries read from the input so far.
+    read: usize,
+    /// Position in the instruction of the account in the first slot.
+    first: usize,
+    slots: NonNull<AccountInfo>,
+    capacity: usize,
+    /// Slots written so far, which are never written again.
+    taken: usize,
+    _slots: PhantomData<&'info mut [AccountInfo]>,
+}
+
+impl<'info> LazyAccounts<'info> {
+    /// Starts reading the accounts of `context`, which no account has been
+    /// read from yet.
+    ///
+    /// # Safety
+    ///
+    /// `context` must read the serialized program input, which must outlive
+    /// `'info`.
+    pub unsafe fn new(context: InstructionContext) -> Self {
+        Self {
+            context,
+            read: 0,
+            first: 0,
+            slots: NonNull::dangling(),
+            capacity: 0,
+            taken: 0,
+            _slots: PhantomData,
+        }
+    }
+
+    /// Returns the number of accounts not read yet.
+    pub fn remaining(&self) -> u64 {
+        self.context.remaining()
+    }
+
+    /// Takes the next account into the next free slot.
+    pub fn take(&mut self) -> Result<&'info AccountInfo, PinoError> {
+        if self.taken == self.capacity {
+            return Err(PinoError::TooManyAccounts);
+        }
+        let account = match self.next_entry().ok_or(PinoError::NotEnoughAccountKeys)? {
+            MaybeAccount::Account(account) => account,
+            MaybeAccount::Duplicated(index) => self
+                .slot(index)
+                .ok_or(PinoError::InvalidDuplicateAccount)?
+                .clone(),
+        };
+
+        // The slot is within `capacity` and was never handed out.
+        let slot = unsafe { self.slots.as_ptr().add(self.taken) };
+        unsafe { slot.write(account) };
+        self.taken += 1;
+        Ok(unsafe { &*slot })
+    }
+
+    fn with_slots(self, slots: &'info mut [MaybeUninit<AccountInfo>]) -> Self {
+        Self {
+            first: self.read,
+            capacity: slots.len(),
+            slots: NonNull::from(slots).cast(),
+            taken: 0,
+            ..self
+        }
+    }
+
+    /// Returns the taken account at `index` of the instruction.
+    fn slot(&self, index: u8) -> Option<&'info AccountInfo> {
+        let slot = (index as usize).checked_sub(self.first)?;
+        (slot < self.taken).then(|| unsafe { &*self.slots.as_ptr().add(slot) })
+    }
+
+    fn next_entry(&mut self) -> Option<MaybeAccount> {
+        if self.context.remaining() == 0 {
+            return None;
+        }
+        self.read += 1;
+        Some(unsafe { self.context.next_account_unchecked() })
+    }
+}
+
+impl Iterator for LazyAccounts<'_> {
+    type Item = MaybeAccount;
+
+    fn next(&mut self) -> Option<MaybeAccount> {
+        match self.next_entry()? {
+            MaybeAccount::Duplicated(index) => Some(match self.slot(index) {
+                Some(account) => MaybeAccount::Account(account.clone()),
+                None => MaybeAccount::Duplicated(index),
+            }),
+            account => Some(account),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Stack storage for the accounts an Accounts struct takes from
+/// [`LazyAccounts`], one slot per field.
+pub struct AccountSlots<const N: usize> {
+    infos: [MaybeUninit<AccountInfo>; N],
+}
+
+impl<const N: usize> AccountSlots<N> {
+    /// Creates empty slots.
+    pub const fn new() -> Self {
+        Self {
+            // An array of `MaybeUninit` needs no initialization.
+            infos: unsafe { MaybeUninit::uninit().assume_init() },
+        }
+    }
+}
+
+impl<const N: usize> Default for AccountSlots<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AsMut<[MaybeUninit<AccountInfo>]> for AccountSlots<N> {
+    fn as_mut(&mut self) -> &mut [MaybeUninit<AccountInfo>] {
+        &mut self.infos
     }
 }
 
+/// Most accounts a CPI made through [`CpiContext`] can pass, which is also
+/// the limit of `slice_invoke_signed`.
+pub const MAX_CPI_ACCOUNTS: usize = 64;
+
 /// Context for Cross-Program Invocations (CPI).
-pub struct CpiContext<'info, T> {
+///
+/// `P` is the marker type of the invoked program and `T` its accounts.
+pub struct CpiContext<'info, 'a, P, T> {
+    /// The invoked program
+    pub program: Program<'info, P>,
     /// The structured accounts for the CPI
     pub accounts: T,
-    /// The remaining accounts for the CPI
+    /// The remaining accounts for the CPI, passed after `accounts`
     pub remaining_accounts: &'info [AccountInfo],
-    /// Signer seeds for PDA signing
-    pub signer_seeds: &'info [&'info [&'info [u8]]],
+    /// Signers for the PDA accounts of the CPI
+    pub signer_seeds: &'a [Signer<'a, 'a>],
 }
 
-impl<'info, T> CpiContext<'info, T> {
+impl<'info, 'a, P, T> CpiContext<'info, 'a, P, T> {
     /// Creates a new CpiContext.
-    pub fn new(accounts: T, remaining_accounts: &'info [AccountInfo]) -> Self {
+    pub fn new(program: Program<'info, P>, accounts: T) -> Self {
         Self {
+            program,
             accounts,
-            remaining_accounts,
+            remaining_accounts: &[],
             signer_seeds: &[],
         }
     }
 
     /// Creates a new CpiContext with signer seeds.
     pub fn new_with_signer(
+        program: Program<'info, P>,
         accounts: T,
-        remaining_accounts: &'info [AccountInfo],
-        signer_seeds: &'info [&'info [&'info [u8]]],
+        signer_seeds: &'a [Signer<'a, 'a>],
     ) -> Self {
         Self {
+            program,
             accounts,
-            remaining_accounts,
+            remaining_accounts: &[],
             signer_seeds,
         }
     }
 
+    /// Sets the signer seeds.
+    pub fn with_signer(mut self, signer_seeds: &'a [Signer<'a, 'a>]) -> Self {
+        self.signer_seeds = signer_seeds;
+        self
+    }
+
+    /// Sets the remaining accounts.
+    pub fn with_remaining_accounts(mut self, remaining_accounts: &'info [AccountInfo]) -> Self {
+        self.remaining_accounts = remaining_accounts;
+        self
+    }
+
     /// Returns the signer seeds.
-    pub fn signer_seeds(&self) -> &[&[&[u8]]] {
+    pub fn signer_seeds(&self) -> &[Signer<'a, 'a>] {
         self.signer_seeds
     }
 }
 
-/// Storage for PDA bump seeds.
-#[derive(Default)]
+impl<'info, P, T: CpiAccounts<'info>> CpiContext<'info, '_, P, T> {
+    /// Invokes the program with `data`, signing with the signer seeds.
+    ///
+    /// Account metas and infos are gathered on the stack, `accounts` first
+    /// and then the remaining accounts.
+    pub fn invoke(&self, data: &[u8]) -> ProgramResult {
+        let mut metas = StackVec::<AccountMeta<'info>, MAX_CPI_ACCOUNTS>::new();
+        let mut infos = StackVec::<&'info AccountInfo, MAX_CPI_ACCOUNTS>::new();
+
+        self.accounts.to_account_metas(&mut metas)?;
+        self.accounts.to_account_infos(&mut infos)?;
+        for account in self.remaining_accounts {
+            metas.push(AccountMeta::new(
+                account.key(),
+                account.is_writable(),
+                account.is_signer(),
+            ))?;
+            infos.push(account)?;
+        }
+
+        let instruction = Instruction {
+            program_id: self.program.key(),
+            accounts: metas.as_slice(),
+            data,
+        };
+        pinocchio::cpi::slice_invoke_signed(&instruction, infos.as_slice(), self.signer_seeds)
+    }
+}
+
+/// Index-based storage for PDA bump seeds.
+///
+/// Derived Accounts structs generate a typed bumps struct instead; this is
+/// available as the `Bumps` type of hand-written [`Bumps`] impls.
+#[derive(Clone, Default)]
 pub struct BumpSeeds {
     seeds: [Option<u8>; 16], // Support up to 16 PDAs per instruction
     count: usize,
@@ -123,7 +407,7 @@ impl BumpSeeds {
     /// Adds a bump seed.
     pub fn add(&mut self, bump: u8) -> Result<usize, PinoError> {
         if self.count >= 16 {
-            return Err(PinoError::Custom(0x1001)); // Too many PDAs
+            return Err(PinoError::TooManyBumpSeeds);
         }
         
         let index = self.count;
@@ -152,17 +436,75 @@ impl BumpSeeds {
     }
 }
 
+/// Names the bump seeds of an accounts struct.
+///
+/// Kept apart from [`Accounts`] so that `Context<T>` does not tie its own
+/// lifetimes to the one `T` is generic over, which lets handlers take
+/// `Context<MyAccounts>` with every lifetime elided.
+pub trait Bumps {
+    /// Bump seeds found while validating PDA accounts.
+    type Bumps: Default + Clone;
+
+    /// Signer seeds of the PDA accounts, kept for CPIs.
+    type SignerSeeds<'info>: SignerSeeds<'info>;
+}
+
+/// Signer seeds of the PDA accounts validated by an accounts struct.
+///
+/// Every `seeds = [...]` account keeps its seeds for the whole instruction,
+/// so each seed expression must borrow for `'info`: constants, account keys
+/// or data, and instruction data rather than temporaries. Other seeds are
+/// rejected at compile time.
+pub trait SignerSeeds<'info>: Default {
+    /// Returns the signer of the PDA at `address`, if it was validated with
+    /// seeds.
+    fn signer_for(&self, address: &Pubkey) -> Option<Signer<'info, '_>>;
+}
+
+/// For accounts structs without PDA accounts.
+impl<'info> SignerSeeds<'info> for () {
+    fn signer_for(&self, _address: &Pubkey) -> Option<Signer<'info, '_>> {
+        None
+    }
+}
+
+/// Every bump value, so that a bump seed can be borrowed for as long as the
+/// other seeds of its signer.
+static BUMP_SEEDS: [u8; 256] = {
+    let mut seeds = [0; 256];
+    let mut bump = 0;
+    while bump < 256 {
+        seeds[bump] = bump as u8;
+        bump += 1;
+    }
+    seeds
+};
+
+/// Returns `bump` as a seed.
+pub fn bump_seed(bump: u8) -> &'static [u8] {
+    core::slice::from_ref(&BUMP_SEEDS[bump as usize])
+}
+
 /// Trait for types that can be used as account contexts.
 ///
 /// This trait is implemented by the derive macro for account structs.
-pub trait Accounts<'info>: Sized {
-    /// Tries to deserialize accounts from the given account infos.
+pub trait Accounts<'info>: Bumps + Sized {
+    /// Storage for the accounts the struct takes lazily, `AccountSlots<N>`
+    /// with one slot per field.
+    type Slots: Default + AsMut<[MaybeUninit<AccountInfo>]>;
+
+    /// Tries to deserialize accounts, taking them from the front of
+    /// `accounts`.
+    ///
+    /// Fails with the error of the System program if creating or resizing
+    /// an account does.
     fn try_accounts(
         program_id: &Pubkey,
-        accounts: &mut &[AccountInfo],
-        instruction_data: &[u8],
-        bumps: &mut BumpSeeds,
-    ) -> Result<Self, PinoError>;
+        accounts: &mut RemainingAccounts<'info>,
+        instruction_data: &'info [u8],
+        bumps: &mut Self::Bumps,
+        signer_seeds: &mut Self::SignerSeeds<'info>,
+    ) -> Result<Self, ProgramError>;
 
     /// Performs any necessary cleanup or validation after instruction execution.
     fn exit(&self, program_id: &Pubkey) -> ProgramResult {
@@ -171,26 +513,38 @@ pub trait Accounts<'info>: Sized {
 }
 
 /// Trait for types that can be used as CPI account contexts.
+///
+/// Both methods append in instruction order, so that the metas and infos
+/// line up.
 pub trait CpiAccounts<'info> {
-    /// Converts to account metas for CPI.
-    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<pinocchio::instruction::AccountMeta>;
+    /// Appends the account metas for CPI.
+    fn to_account_metas<const N: usize>(
+        &self,
+        metas: &mut StackVec<AccountMeta<'info>, N>,
+    ) -> Result<(), PinoError>;
 
-    /// Returns the account infos for CPI.
-    fn to_account_infos(&self) -> Vec<AccountInfo>;
+    /// Appends the account infos for CPI.
+    fn to_account_infos<const N: usize>(
+        &self,
+        infos: &mut StackVec<&'info AccountInfo, N>,
+    ) -> Result<(), PinoError>;
 }
 
 /// Helper trait for instruction data deserialization.
-pub trait InstructionData: Sized {
+///
+/// The lifetime lets `#[derive(PinoData)]` types borrow from the instruction
+/// data instead of copying it.
+pub trait InstructionData<'a>: Sized {
     /// Deserializes instruction data.
-    fn try_from_slice(data: &[u8]) -> Result<Self, PinoError>;
+    fn try_from_slice(data: &'a [u8]) -> Result<Self, PinoError>;
 }
 
 /// Implements InstructionData for types that implement borsh::BorshDeserialize.
-impl<T> InstructionData for T
+impl<'a, T> InstructionData<'a> for T
 where
     T: borsh::BorshDeserialize,
 {
-    fn try_from_slice(data: &[u8]) -> Result<Self, PinoError> {
+    fn try_from_slice(data: &'a [u8]) -> Result<Self, PinoError> {
         borsh::BorshDeserialize::try_from_slice(data)
             .map_err(|_| PinoError::InvalidInstructionData)
     }
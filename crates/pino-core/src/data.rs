@@ -0,0 +1,158 @@
+//! Zero-copy instruction data.
+//!
+//! `#[derive(PinoData)]` generates a [`PinoData`] implementation that parses
+//! instruction data into a view borrowing from the input, and a serializer
+//! producing the same bytes. The wire format is:
+//!
+//! - fixed-size fields (any [`Pod`] type) as their little-endian bytes, read
+//!   unaligned;
+//! - `&[u8]` and `&str` fields as a `u32` little-endian length followed by the
+//!   bytes.
+//!
+//! For integers, byte arrays, byte slices and strings this matches Borsh, so
+//! existing clients keep working.
+
+extern crate alloc;
+
+use bytemuck::Pod;
+use core::mem::size_of;
+
+use crate::{collections::StackVec, error::PinoError};
+
+// Named by the generated `ToInstructionData` impls.
+#[doc(hidden)]
+pub use alloc::vec::Vec;
+
+/// Instruction data that is decoded without copying variable-length fields.
+///
+/// Implemented by `#[derive(PinoData)]`.
+pub trait PinoData<'a>: Sized {
+    /// Parses a view over `data`, which must be consumed exactly.
+    fn parse(data: &'a [u8]) -> Result<Self, PinoError>;
+
+    /// Returns the number of bytes [`PinoData::write_to`] produces.
+    fn serialized_len(&self) -> usize;
+
+    /// Serializes into `buffer`, returning the number of bytes written.
+    fn write_to(&self, buffer: &mut [u8]) -> Result<usize, PinoError>;
+
+    /// Serializes into a freshly allocated buffer.
+    fn to_vec(&self) -> Result<Vec<u8>, PinoError> {
+        let mut buffer = alloc::vec![0u8; self.serialized_len()];
+        self.write_to(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Serializes at the end of `buffer`, without allocating.
+    fn write_to_stack_vec<const N: usize>(
+        &self,
+        buffer: &mut StackVec<u8, N>,
+    ) -> Result<(), PinoError> {
+        let start = buffer.len();
+        buffer.resize(start + self.serialized_len(), 0)?;
+        self.write_to(&mut buffer.as_mut_slice()[start..])?;
+        Ok(())
+    }
+}
+
+/// Size of the length prefix of variable-length fields.
+pub const LENGTH_PREFIX_SIZE: usize = size_of::<u32>();
+
+/// Cursor over instruction data used by generated parsers.
+pub struct DataReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DataReader<'a> {
+    /// Creates a reader over `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the bytes that have not been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Reads a fixed-size value, regardless of alignment.
+    pub fn read<T: Pod>(&mut self) -> Result<T, PinoError> {
+        let bytes = self.take(size_of::<T>())?;
+        Ok(bytemuck::pod_read_unaligned(bytes))
+    }
+
+    /// Reads length-prefixed bytes, borrowing them from the input.
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], PinoError> {
+        let len = u32::from_le_bytes(self.read::<[u8; 4]>()?) as usize;
+        self.take(len)
+    }
+
+    /// Reads a length-prefixed UTF-8 string, borrowing it from the input.
+    pub fn read_str(&mut self) -> Result<&'a str, PinoError> {
+        let bytes = self.read_bytes()?;
+        core::str::from_utf8(bytes).map_err(|_| PinoError::InvalidInstructionData)
+    }
+
+    /// Ensures the whole input has been consumed.
+    pub fn finish(self) -> Result<(), PinoError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(PinoError::InvalidInstructionData)
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PinoError> {
+        if self.data.len() < len {
+            return Err(PinoError::InvalidInstructionData);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+}
+
+/// Cursor over an output buffer used by generated serializers.
+pub struct DataWriter<'b> {
+    buffer: &'b mut [u8],
+    position: usize,
+}
+
+impl<'b> DataWriter<'b> {
+    /// Creates a writer at the start of `buffer`.
+    pub fn new(buffer: &'b mut [u8]) -> Self {
+        Self { buffer, position: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Writes a fixed-size value.
+    pub fn write<T: Pod>(&mut self, value: &T) -> Result<(), PinoError> {
+        self.put(bytemuck::bytes_of(value))
+    }
+
+    /// Writes length-prefixed bytes.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PinoError> {
+        let len = u32::try_from(bytes.len()).map_err(|_| PinoError::InvalidInstructionData)?;
+        self.put(&len.to_le_bytes())?;
+        self.put(bytes)
+    }
+
+    /// Writes a length-prefixed UTF-8 string.
+    pub fn write_str(&mut self, value: &str) -> Result<(), PinoError> {
+        self.write_bytes(value.as_bytes())
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), PinoError> {
+        let end = self.position + bytes.len();
+        let target = self
+            .buffer
+            .get_mut(self.position..end)
+            .ok_or(PinoError::InvalidInstructionData)?;
+        target.copy_from_slice(bytes);
+        self.position = end;
+        Ok(())
+    }
+}
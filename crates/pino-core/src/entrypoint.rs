@@ -6,25 +6,41 @@
 use pinocchio::{
     account_info::AccountInfo,
     entrypoint::InstructionContext,
+    program_error::ProgramError,
     pubkey::Pubkey,
     ProgramResult,
 };
 use crate::{
-    context::{Context, BumpSeeds, Accounts},
+    context::{Accounts, Bumps, Context, LazyAccounts, RemainingAccounts},
     error::PinoError,
-    instruction::InstructionData,
 };
 
 /// Trait for program instruction processors.
 ///
-/// This trait should be implemented by your program's instruction enum.
-pub trait ProgramInstruction: InstructionData {
-    /// Processes the instruction with the given accounts and context.
-    fn process<'info>(
-        &self,
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        instruction_data: &[u8],
+/// Instruction data is read in a single pass: `decode` splits the
+/// discriminator off and decodes the arguments of the instruction it
+/// selects, then `process` hands them to the handler. `#[pino_processor]`
+/// implements this trait, with the generated `<Processor>Instruction` enum
+/// as the decoded instruction.
+pub trait ProgramInstruction {
+    /// The decoded instruction, borrowing its arguments from the instruction
+    /// data.
+    type Instruction<'a>;
+
+    /// Decodes the instruction selected by the instruction data.
+    ///
+    /// Returns the instruction and the bytes of its arguments.
+    fn decode(instruction_data: &[u8]) -> Result<(Self::Instruction<'_>, &[u8]), PinoError>;
+
+    /// Processes a decoded instruction with the given accounts.
+    ///
+    /// `accounts` holds every account of the instruction, none taken yet.
+    /// `instruction_data` holds the argument bytes returned by `decode`.
+    fn process<'a>(
+        instruction: Self::Instruction<'a>,
+        program_id: &'a Pubkey,
+        accounts: RemainingAccounts<'a>,
+        instruction_data: &'a [u8],
     ) -> ProgramResult;
 }
 
@@ -38,120 +54,312 @@ pub fn process_instruction<T: ProgramInstruction>(
     instruction_data: &[u8],
 ) -> ProgramResult {
     // Deserialize the instruction
-    let instruction = T::try_from_slice(instruction_data)
-        .map_err(|e| pinocchio::program_error::ProgramError::from(e))?;
+    let (instruction, instruction_data) = T::decode(instruction_data)?;
 
     // Process the instruction
-    instruction.process(program_id, accounts, instruction_data)
+    T::process(instruction, program_id, RemainingAccounts::Parsed(accounts), instruction_data)
 }
 
 /// Processes a program instruction using Pino's lazy interface.
 ///
-/// This provides maximum CU efficiency by only parsing accounts as needed.
-pub fn process_instruction_lazy<T: ProgramInstruction>(
-    mut ctx: InstructionContext,
-) -> ProgramResult {
-    // Get program ID and instruction data
-    let program_id = unsafe { ctx.program_id_unchecked() };
-    let instruction_data = ctx.instruction_data()
-        .map_err(|e| pinocchio::program_error::ProgramError::from(e))?;
+/// Accounts are left in the program input and read as the Accounts struct
+/// of the instruction takes them, so there is no limit on their number and
+/// no copy of the ones a handler never looks at.
+///
+/// # Safety
+///
+/// `input` must point to the serialized program input.
+pub unsafe fn process_instruction_lazy<T: ProgramInstruction>(input: *mut u8) -> ProgramResult {
+    // The instruction data follows the accounts, so each account is stepped
+    // over once to reach it. Nothing is kept from this pass; accounts are
+    // read again only as far as the handler takes them.
+    let mut scan = InstructionContext::new_unchecked(input);
+    while scan.remaining() > 0 {
+        scan.next_account_unchecked();
+    }
+    let program_id = scan.program_id_unchecked();
+    let instruction_data = scan.instruction_data_unchecked();
 
     // Deserialize the instruction
-    let instruction = T::try_from_slice(instruction_data)
-        .map_err(|e| pinocchio::program_error::ProgramError::from(e))?;
-
-    // Collect all accounts
-    let mut accounts = heapless::Vec::<AccountInfo, 64>::new();
-    let mut account_iter = ctx.accounts();
-    while let Ok(maybe_account) = account_iter.next() {
-        match maybe_account {
-            pinocchio::entrypoint::MaybeAccount::Account(account) => {
-                accounts.push(account).map_err(|_| {
-                    pinocchio::program_error::ProgramError::Custom(0x1002) // Too many accounts
-                })?;
-            }
-            pinocchio::entrypoint::MaybeAccount::Duplicated(index) => {
-                if let Some(original) = accounts.get(index as usize) {
-                    accounts.push(original.clone()).map_err(|_| {
-                        pinocchio::program_error::ProgramError::Custom(0x1002)
-                    })?;
-                } else {
-                    return Err(pinocchio::program_error::ProgramError::Custom(0x1003)); // Invalid duplicate index
-                }
-            }
-        }
-    }
+    let (instruction, instruction_data) = T::decode(instruction_data)?;
 
     // Process the instruction
-    instruction.process(program_id, &accounts, instruction_data)
+    T::process(
+        instruction,
+        program_id,
+        RemainingAccounts::Lazy(LazyAccounts::new(InstructionContext::new_unchecked(input))),
+        instruction_data,
+    )
+}
+
+/// Accounts parsed by [`parse_accounts`], owned outside the [`Context`] that
+/// borrows them so that they outlive the handler for `Accounts::exit`.
+pub struct ParsedAccounts<'info, T: Bumps> {
+    /// The structured accounts
+    pub accounts: T,
+    /// The remaining accounts not captured in the accounts struct
+    pub remaining_accounts: RemainingAccounts<'info>,
+    /// Bump seeds of the PDA accounts, by field name
+    pub bumps: T::Bumps,
+    /// Signer seeds of the PDA accounts
+    pub signer_seeds: T::SignerSeeds<'info>,
 }
 
-/// Helper function to parse accounts into a structured context.
+impl<'info, T: Bumps> ParsedAccounts<'info, T> {
+    /// Returns a context borrowing the accounts.
+    ///
+    /// The remaining accounts and signer seeds move into the first context;
+    /// later ones, such as the one `after` hooks get, have none.
+    pub fn context<'a>(
+        &'a mut self,
+        program_id: &'info Pubkey,
+        instruction_data: &'info [u8],
+    ) -> Context<'a, 'info, T> {
+        Context {
+            program_id,
+            accounts: &mut self.accounts,
+            remaining_accounts: core::mem::replace(
+                &mut self.remaining_accounts,
+                RemainingAccounts::Parsed(&[]),
+            ),
+            instruction_data,
+            bumps: self.bumps.clone(),
+            signer_seeds: core::mem::take(&mut self.signer_seeds),
+        }
+    }
+}
+
+/// Helper function to parse the accounts of an instruction.
+///
+/// Accounts read lazily are stored in `slots`, which are left untouched by
+/// parsed accounts:
+///
+/// ```ignore
+/// let mut slots = Default::default();
+/// let mut parsed = parse_accounts::<Initialize>(program_id, accounts, &mut slots, data)?;
+/// ```
 pub fn parse_accounts<'info, T: Accounts<'info>>(
     program_id: &'info Pubkey,
-    accounts: &mut &'info [AccountInfo],
+    accounts: RemainingAccounts<'info>,
+    slots: &'info mut T::Slots,
     instruction_data: &'info [u8],
-) -> Result<Context<'info, T>, PinoError> {
-    let mut bumps = BumpSeeds::new();
-    
+) -> Result<ParsedAccounts<'info, T>, ProgramError> {
+    let mut bumps = T::Bumps::default();
+    let mut signer_seeds = T::SignerSeeds::default();
+    let mut accounts = accounts.with_slots(slots.as_mut());
+
     // Parse the accounts using the Accounts trait
-    let parsed_accounts = T::try_accounts(program_id, accounts, instruction_data, &mut bumps)?;
-    
-    // Create the context
-    Ok(Context {
+    let parsed_accounts = T::try_accounts(
         program_id,
+        &mut accounts,
+        instruction_data,
+        &mut bumps,
+        &mut signer_seeds,
+    )?;
+
+    Ok(ParsedAccounts {
         accounts: parsed_accounts,
         remaining_accounts: accounts,
-        instruction_data,
         bumps,
+        signer_seeds,
     })
 }
 
 /// Macro to generate a program entrypoint with automatic instruction routing.
+///
+/// `pino_entrypoint!(MyProcessor)` accepts up to 64 accounts and installs
+/// Pinocchio's default allocator and panic handler. The key-value form
+/// configures each of them; every key but `processor` is optional, and keys
+/// must appear in this order:
+///
+/// ```ignore
+/// pino_entrypoint! {
+///     processor: MyProcessor,
+///     errors: MyError,         // the #[error_code] enum whose codes are logged
+///     max_accounts: 16,
+///     allocator: region(4096), // bump, region(size), pool(block_size), stack or none
+///     heap_size: 64 * 1024,    // defaults to 32 KiB
+///     panic_handler: pino,     // default, nostd, pino, pino_nostd or none
+/// }
+/// ```
+///
+/// The `bump`, `region`, `pool` and `stack` allocators are the heap
+/// allocators of `pino_runtime`, which the program must then depend on. A
+/// heap larger than 32 KiB must be requested by the transaction with a
+/// `RequestHeapFrame` instruction.
+///
+/// The `pino` panic handlers log the panic location and message with the
+/// compute units left, or only a fixed line with the `strip-panic-logs`
+/// feature; `pino_nostd` is the one for `no_std` programs.
 #[macro_export]
 macro_rules! pino_entrypoint {
-    ($processor:ty) => {
+    (
+        processor: $processor:ty
+        $(, errors: $errors:ty)?
+        $(, max_accounts: $max_accounts:expr)?
+        $(, allocator: $allocator:ident $(($allocator_arg:expr))?)?
+        $(, heap_size: $heap_size:expr)?
+        $(, panic_handler: $panic_handler:ident)?
+        $(,)?
+    ) => {
         /// Program entrypoint using Pino's high-level interface
         #[no_mangle]
         pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
             use $crate::entrypoint::process_instruction;
-            
+
+            const MAX_ACCOUNTS: usize = $crate::__pino_or!($($max_accounts)?, 64);
+
             // Use Pinocchio's standard entrypoint for parsing
-            let (program_id, accounts, instruction_data) = 
-                pinocchio::entrypoint::deserialize::<64>(input, &mut [
-                    core::mem::MaybeUninit::uninit(); 64
+            let (program_id, accounts, instruction_data) =
+                $crate::pinocchio::entrypoint::deserialize::<MAX_ACCOUNTS>(input, &mut [
+                    ::core::mem::MaybeUninit::uninit(); MAX_ACCOUNTS
                 ]);
 
             match process_instruction::<$processor>(
                 &program_id,
-                core::slice::from_raw_parts(accounts.as_ptr() as _, accounts.len()),
+                ::core::slice::from_raw_parts(accounts.as_ptr() as _, accounts.len()),
                 &instruction_data,
             ) {
                 Ok(()) => $crate::SUCCESS,
-                Err(error) => error.into(),
+                Err(error) => {
+                    $crate::error::log_program_error::<$crate::__pino_errors!($($errors)?)>(&error);
+                    error.into()
+                }
             }
         }
-        
-        // Set up allocator and panic handler
-        pinocchio::default_allocator!();
-        pinocchio::default_panic_handler!();
+
+        $crate::__pino_allocator!($($allocator $(($allocator_arg))?)?; $($heap_size)?);
+        $crate::__pino_panic_handler!($($panic_handler)?);
+    };
+    ($processor:ty) => {
+        $crate::pino_entrypoint! { processor: $processor }
+    };
+}
+
+/// Expands to the first argument if given, and to the default otherwise.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pino_or {
+    (, $default:expr) => {
+        $default
+    };
+    ($value:expr, $default:expr) => {
+        $value
+    };
+}
+
+/// Expands to the error enum given to an entrypoint macro, or to `()`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pino_errors {
+    () => {
+        ()
+    };
+    ($errors:ty) => {
+        $errors
+    };
+}
+
+/// Installs the allocator selected in `pino_entrypoint!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pino_allocator {
+    (;) => {
+        $crate::pinocchio::default_allocator!();
+    };
+    (none;) => {
+        $crate::pinocchio::no_allocator!();
+    };
+    (bump; $($heap_size:expr)?) => {
+        $crate::__pino_allocator!(@install ::pino_runtime::BumpHeap, $($heap_size)?;);
+    };
+    (stack; $($heap_size:expr)?) => {
+        $crate::__pino_allocator!(@install ::pino_runtime::StackHeap, $($heap_size)?;);
+    };
+    (region($region_size:expr); $($heap_size:expr)?) => {
+        $crate::__pino_allocator!(@install ::pino_runtime::RegionHeap, $($heap_size)?; $region_size);
+    };
+    (pool($block_size:expr); $($heap_size:expr)?) => {
+        $crate::__pino_allocator!(@install ::pino_runtime::PoolHeap, $($heap_size)?; $block_size);
+    };
+    (@install $allocator:path, $($heap_size:expr)?; $($arg:expr)?) => {
+        #[cfg(target_os = "solana")]
+        #[global_allocator]
+        static __PINO_ALLOCATOR: $allocator = <$allocator>::new(
+            $crate::__pino_or!($($heap_size)?, ::pino_runtime::allocator::heap::DEFAULT_HEAP_SIZE)
+            $(, $arg)?
+        );
+    };
+    ($($allocator:ident)?; $heap_size:expr) => {
+        ::core::compile_error!("`heap_size` requires one of the `bump`, `region`, `pool` or `stack` allocators");
+    };
+    ($($other:tt)*) => {
+        ::core::compile_error!("unknown allocator; expected `bump`, `region(size)`, `pool(block_size)`, `stack` or `none`");
+    };
+}
+
+/// Installs the panic handler selected in `pino_entrypoint!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pino_panic_handler {
+    () => {
+        $crate::pinocchio::default_panic_handler!();
+    };
+    (default) => {
+        $crate::pinocchio::default_panic_handler!();
+    };
+    (nostd) => {
+        $crate::pinocchio::nostd_panic_handler!();
+    };
+    (pino) => {
+        /// Panic hook logging the location, message and compute units left.
+        #[cfg(target_os = "solana")]
+        #[no_mangle]
+        fn custom_panic(info: &::core::panic::PanicInfo<'_>) {
+            $crate::panic::log_panic(info);
+        }
+    };
+    (pino_nostd) => {
+        /// Panic handler logging the location, message and compute units left.
+        #[cfg(target_os = "solana")]
+        #[panic_handler]
+        fn handler(info: &::core::panic::PanicInfo<'_>) -> ! {
+            $crate::panic::log_panic(info);
+            unsafe { $crate::pinocchio::syscalls::abort() }
+        }
+
+        // Off-chain builds take the panic handler of `std`.
+        #[cfg(not(target_os = "solana"))]
+        mod __pino_panic_handler {
+            extern crate std as __std;
+        }
+    };
+    (none) => {};
+    ($other:ident) => {
+        ::core::compile_error!(
+            "unknown panic handler; expected `default`, `nostd`, `pino`, `pino_nostd` or `none`"
+        );
     };
 }
 
 /// Macro to generate a lazy program entrypoint for maximum CU efficiency.
+///
+/// `pino_lazy_entrypoint!(MyProcessor, errors: MyError)` logs the codes of
+/// an `#[error_code]` enum, as in [`pino_entrypoint!`].
 #[macro_export]
 macro_rules! pino_lazy_entrypoint {
-    ($processor:ty) => {
+    ($processor:ty $(, errors: $errors:ty)? $(,)?) => {
         /// Program entrypoint using Pino's lazy interface
         #[no_mangle]
         pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
             use $crate::entrypoint::process_instruction_lazy;
             
-            let ctx = pinocchio::entrypoint::InstructionContext::new_unchecked(input);
-            
-            match process_instruction_lazy::<$processor>(ctx) {
+            match process_instruction_lazy::<$processor>(input) {
                 Ok(()) => $crate::SUCCESS,
-                Err(error) => error.into(),
+                Err(error) => {
+                    $crate::error::log_program_error::<$crate::__pino_errors!($($errors)?)>(&error);
+                    error.into()
+                }
             }
         }
         
@@ -162,19 +370,23 @@ macro_rules! pino_lazy_entrypoint {
 }
 
 /// Macro to generate a no-allocator program entrypoint.
+///
+/// `pino_no_alloc_entrypoint!(MyProcessor, errors: MyError)` logs the codes
+/// of an `#[error_code]` enum, as in [`pino_entrypoint!`].
 #[macro_export]
 macro_rules! pino_no_alloc_entrypoint {
-    ($processor:ty) => {
+    ($processor:ty $(, errors: $errors:ty)? $(,)?) => {
         /// Program entrypoint with no allocator
         #[no_mangle]
         pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
             use $crate::entrypoint::process_instruction_lazy;
             
-            let ctx = pinocchio::entrypoint::InstructionContext::new_unchecked(input);
-            
-            match process_instruction_lazy::<$processor>(ctx) {
+            match process_instruction_lazy::<$processor>(input) {
                 Ok(()) => $crate::SUCCESS,
-                Err(error) => error.into(),
+                Err(error) => {
+                    $crate::error::log_program_error::<$crate::__pino_errors!($($errors)?)>(&error);
+                    error.into()
+                }
             }
         }
         
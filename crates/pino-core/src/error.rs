@@ -3,83 +3,309 @@
 //! This module provides efficient error types and validation macros that
 //! compile to minimal CU overhead while maintaining type safety.
 
-use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+use core::fmt;
 
-/// Main error type for Pino programs.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PinoError {
+use pinocchio::program_error::ProgramError;
+
+/// First code reserved for framework errors.
+pub const ERROR_CODE_MIN: u32 = 1000;
+
+/// Last code reserved for framework errors. Program errors declared with
+/// `#[error_code]` start right after, at 6000.
+pub const ERROR_CODE_MAX: u32 = 5999;
+
+/// Declares the framework error variants along with their codes.
+///
+/// The codes also become the discriminants of a private enum, so assigning
+/// the same code twice fails to compile.
+macro_rules! pino_errors {
+    ($(#[doc = $message:literal] $name:ident = $code:literal,)*) => {
+        /// Main error type for Pino programs.
+        ///
+        /// Every variant but [`PinoError::Custom`] returns its own code from
+        /// the reserved [`ERROR_CODE_MIN`]`..=`[`ERROR_CODE_MAX`] range as
+        /// `ProgramError::Custom`, which [`PinoError::from_code`] decodes.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum PinoError {
+            $(#[doc = $message] $name,)*
+            /// Custom error with code
+            Custom(u32),
+        }
+
+        #[repr(u32)]
+        #[allow(dead_code)]
+        enum Code {
+            $($name = $code,)*
+        }
+
+        impl PinoError {
+            /// Returns the code carried by `ProgramError::Custom`.
+            pub const fn code(&self) -> u32 {
+                match self {
+                    $(PinoError::$name => Code::$name as u32,)*
+                    PinoError::Custom(code) => *code,
+                }
+            }
+
+            /// Returns the variant name.
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(PinoError::$name => stringify!($name),)*
+                    PinoError::Custom(_) => "Custom",
+                }
+            }
+
+            /// Decodes a code returned by a Pino program.
+            ///
+            /// Codes outside the framework range come back as
+            /// [`PinoError::Custom`].
+            pub const fn from_code(code: u32) -> Self {
+                match code {
+                    $($code => PinoError::$name,)*
+                    code => PinoError::Custom(code),
+                }
+            }
+
+            /// Returns the line logged when the error fails an instruction,
+            /// assembled at compile time.
+            #[cfg(not(feature = "strip-error-logs"))]
+            const fn log_line(&self) -> Option<&'static str> {
+                match self {
+                    $(PinoError::$name => Some(concat!(
+                        "Error Code: ", stringify!($name),
+                        ". Error Number: ", $code,
+                        ". Error Message:", $message, "."
+                    )),)*
+                    PinoError::Custom(_) => None,
+                }
+            }
+        }
+    };
+}
+
+pino_errors! {
+    // 1000: instructions
+    /// Instruction data is invalid or malformed
+    InvalidInstructionData = 1000,
+    /// No instruction matches the discriminator and there is no fallback
+    InstructionFallbackNotFound = 1001,
+
+    // 2000: account constraints
+    /// A `has_one` constraint was violated
+    ConstraintHasOne = 2000,
+    /// An `address` constraint was violated
+    ConstraintAddress = 2001,
+    /// A `constraint = expr` check evaluated to false
+    ConstraintRaw = 2002,
+    /// Account address does not match the PDA derived from its seeds
+    InvalidSeeds = 2003,
+    /// Account was not validated with seeds, so there is no signer for it
+    NoSignerSeeds = 2004,
+
+    // 3000: accounts
     /// Account data is too small for the expected type
-    AccountDataTooSmall,
+    AccountDataTooSmall = 3000,
     /// Account is not mutable when mutation is required
-    AccountNotMutable,
+    AccountNotMutable = 3001,
     /// Account is not a signer when signature is required
-    AccountNotSigner,
+    AccountNotSigner = 3002,
     /// Account is not executable when program account is expected
-    AccountNotExecutable,
+    AccountNotExecutable = 3003,
     /// Invalid program ID
-    InvalidProgramId,
+    InvalidProgramId = 3004,
     /// Account borrow failed (already borrowed)
-    AccountBorrowFailed,
-    /// Instruction data is invalid or malformed
-    InvalidInstructionData,
-    /// Insufficient funds for operation
-    InsufficientFunds,
+    AccountBorrowFailed = 3005,
     /// Account already initialized
-    AccountAlreadyInitialized,
+    AccountAlreadyInitialized = 3006,
     /// Account not initialized
-    AccountNotInitialized,
+    AccountNotInitialized = 3007,
     /// Invalid account owner
-    InvalidAccountOwner,
+    InvalidAccountOwner = 3008,
+    /// Account data does not start with the expected discriminator
+    AccountDiscriminatorMismatch = 3009,
+    /// Fewer accounts were provided than the instruction requires
+    NotEnoughAccountKeys = 3010,
+    /// More accounts were provided than the entrypoint can hold
+    TooManyAccounts = 3011,
+    /// A duplicated account points at an account that was not provided
+    InvalidDuplicateAccount = 3012,
+
+    // 4000: runtime
+    /// Insufficient funds for operation
+    InsufficientFunds = 4000,
     /// Arithmetic overflow
-    ArithmeticOverflow,
-    /// Custom error with code
-    Custom(u32),
+    ArithmeticOverflow = 4001,
+    /// A cross-program invocation could not be issued
+    CpiFailed = 4002,
+    /// A sysvar could not be read
+    SysvarUnavailable = 4003,
+    /// More PDA bumps were stored than `BumpSeeds` can hold
+    TooManyBumpSeeds = 4004,
+
+    // 5000: collections and utilities
+    /// A `StackVec` is at capacity
+    StackVecFull = 5000,
+    /// A `StackMap` is at capacity
+    StackMapFull = 5001,
+    /// A `StackString` is at capacity
+    StackStringFull = 5002,
+    /// A value is outside the accepted range
+    ValueOutOfRange = 5003,
+    /// A slice that must not be empty is empty
+    EmptyInput = 5004,
+    /// A string contains non-ASCII bytes
+    NonAsciiString = 5005,
+}
+
+impl fmt::Display for PinoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinoError::Custom(code) => write!(f, "Custom({})", code),
+            error => f.write_str(error.name()),
+        }
+    }
 }
 
 impl From<PinoError> for ProgramError {
+    #[cfg_attr(feature = "debug-errors", track_caller)]
     fn from(error: PinoError) -> Self {
-        match error {
-            PinoError::AccountDataTooSmall => ProgramError::AccountDataTooSmall,
-            PinoError::AccountNotMutable => ProgramError::InvalidAccountData,
-            PinoError::AccountNotSigner => ProgramError::MissingRequiredSignature,
-            PinoError::AccountNotExecutable => ProgramError::InvalidAccountData,
-            PinoError::InvalidProgramId => ProgramError::IncorrectProgramId,
-            PinoError::AccountBorrowFailed => ProgramError::AccountBorrowFailed,
-            PinoError::InvalidInstructionData => ProgramError::InvalidInstructionData,
-            PinoError::InsufficientFunds => ProgramError::InsufficientFunds,
-            PinoError::AccountAlreadyInitialized => ProgramError::AccountAlreadyInitialized,
-            PinoError::AccountNotInitialized => ProgramError::UninitializedAccount,
-            PinoError::InvalidAccountOwner => ProgramError::InvalidAccountOwner,
-            PinoError::ArithmeticOverflow => ProgramError::ArithmeticOverflow,
-            PinoError::Custom(code) => ProgramError::Custom(code),
-        }
+        #[cfg(feature = "debug-errors")]
+        log_location(&error, core::panic::Location::caller());
+        ProgramError::Custom(error.code())
     }
 }
 
 impl From<PinoError> for u64 {
+    #[cfg_attr(feature = "debug-errors", track_caller)]
     fn from(error: PinoError) -> Self {
-        let program_error: ProgramError = error.into();
-        program_error.into()
+        ProgramError::from(error).into()
+    }
+}
+
+/// Program error enums whose codes the entrypoint can name in its log,
+/// implemented by `#[error_code]`.
+///
+/// Entrypoints take the enum as their `errors` option; `()` knows no codes.
+pub trait ProgramErrors {
+    /// Returns the line logged when the error with `code` fails an
+    /// instruction, if `code` belongs to this enum.
+    fn log_line(code: u32) -> Option<&'static str>;
+}
+
+/// For programs without an `#[error_code]` enum.
+impl ProgramErrors for () {
+    fn log_line(_code: u32) -> Option<&'static str> {
+        None
     }
 }
 
+/// Logs the name, code and description of the error that failed the
+/// instruction, as the generated entrypoints do before returning.
+///
+/// Framework codes are looked up in [`PinoError`] and the others in the
+/// program's error enum `E`; codes neither knows are left to the runtime,
+/// which logs every failing code. Compiles to nothing with the
+/// `strip-error-logs` feature.
+#[cold]
+#[allow(unused_variables)]
+pub fn log_program_error<E: ProgramErrors>(error: &ProgramError) {
+    #[cfg(not(feature = "strip-error-logs"))]
+    if let ProgramError::Custom(code) = error {
+        let line = match PinoError::from_code(*code) {
+            PinoError::Custom(code) => E::log_line(code),
+            error => error.log_line(),
+        };
+        if let Some(line) = line {
+            pinocchio::log::sol_log(line);
+        }
+    }
+}
+
+/// Logs `<name> at <file>:<line>` for a failed `require_check` or an error
+/// being converted, e.g. by `?`.
+#[cfg(feature = "debug-errors")]
+#[cold]
+fn log_location(error: &PinoError, location: &core::panic::Location<'_>) {
+    let mut line = crate::collections::StackString::<256>::new();
+    let _ = line.push_str(error.name());
+    if let PinoError::Custom(code) = error {
+        let _ = line.push('(');
+        let _ = line.push_number(*code as u64);
+        let _ = line.push(')');
+    }
+    let _ = line.push_str(" at ");
+    let _ = line.push_str(location.file());
+    let _ = line.push(':');
+    let _ = line.push_number(location.line() as u64);
+    pinocchio::log::sol_log(line.as_str());
+}
+
+/// Logs why a `require_*` check failed when the `debug-errors` feature is on,
+/// and expands to nothing otherwise.
+#[cfg(feature = "debug-errors")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_failed {
+    ($check:literal, $($expr:tt)*) => {
+        $crate::pinocchio::msg!(concat!(
+            $check, " failed: ", stringify!($($expr)*), " at ", file!(), ":", line!()
+        ));
+    };
+}
+
+/// Logs why a `require_*` check failed when the `debug-errors` feature is on,
+/// and expands to nothing otherwise.
+#[cfg(not(feature = "debug-errors"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_failed {
+    ($($tokens:tt)*) => {};
+}
+
+/// Logs both sides of a failed key comparison when the `debug-errors`
+/// feature is on, and expands to nothing otherwise.
+#[cfg(feature = "debug-errors")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_keys_failed {
+    ($left:expr, $right:expr) => {
+        $crate::pinocchio::pubkey::log(&$left);
+        $crate::pinocchio::pubkey::log(&$right);
+    };
+}
+
+/// Logs both sides of a failed key comparison when the `debug-errors`
+/// feature is on, and expands to nothing otherwise.
+#[cfg(not(feature = "debug-errors"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_keys_failed {
+    ($($tokens:tt)*) => {};
+}
+
 /// Validates a condition and returns an error if false.
 ///
 /// This macro compiles to minimal CU overhead - just a conditional jump.
+/// With the `debug-errors` feature, the failing condition and its location
+/// are logged first.
 #[macro_export]
 macro_rules! require {
     ($condition:expr, $error:expr) => {
         if !($condition) {
+            $crate::__require_failed!("require!", $condition);
             return Err($error);
         }
     };
 }
 
 /// Helper function to validate conditions and return Result
+#[cfg_attr(feature = "debug-errors", track_caller)]
 pub fn require_check(condition: bool, error: PinoError) -> Result<(), PinoError> {
     if condition {
         Ok(())
     } else {
+        #[cfg(feature = "debug-errors")]
+        log_location(&error, core::panic::Location::caller());
         Err(error)
     }
 }
@@ -89,6 +315,7 @@ pub fn require_check(condition: bool, error: PinoError) -> Result<(), PinoError>
 macro_rules! require_eq {
     ($left:expr, $right:expr, $error:expr) => {
         if $left != $right {
+            $crate::__require_failed!("require_eq!", $left == $right);
             return Err($error);
         }
     };
@@ -99,26 +326,35 @@ macro_rules! require_eq {
 macro_rules! require_neq {
     ($left:expr, $right:expr, $error:expr) => {
         if $left == $right {
+            $crate::__require_failed!("require_neq!", $left != $right);
             return Err($error);
         }
     };
 }
 
 /// Validates that two public keys are equal.
+///
+/// With the `debug-errors` feature, both keys are logged on failure.
 #[macro_export]
 macro_rules! require_keys_eq {
     ($left:expr, $right:expr, $error:expr) => {
         if $left != $right {
+            $crate::__require_failed!("require_keys_eq!", $left == $right);
+            $crate::__require_keys_failed!($left, $right);
             return Err($error);
         }
     };
 }
 
 /// Validates that two public keys are not equal.
+///
+/// With the `debug-errors` feature, both keys are logged on failure.
 #[macro_export]
 macro_rules! require_keys_neq {
     ($left:expr, $right:expr, $error:expr) => {
         if $left == $right {
+            $crate::__require_failed!("require_keys_neq!", $left != $right);
+            $crate::__require_keys_failed!($left, $right);
             return Err($error);
         }
     };
@@ -129,6 +365,7 @@ macro_rules! require_keys_neq {
 macro_rules! require_owned_by {
     ($account:expr, $owner:expr, $error:expr) => {
         if !$account.is_owned_by($owner) {
+            $crate::__require_failed!("require_owned_by!", $account.is_owned_by($owner));
             return Err($error);
         }
     };
@@ -139,6 +376,7 @@ macro_rules! require_owned_by {
 macro_rules! require_signer {
     ($account:expr, $error:expr) => {
         if !$account.is_signer() {
+            $crate::__require_failed!("require_signer!", $account.is_signer());
             return Err($error);
         }
     };
@@ -149,6 +387,7 @@ macro_rules! require_signer {
 macro_rules! require_writable {
     ($account:expr, $error:expr) => {
         if !$account.is_writable() {
+            $crate::__require_failed!("require_writable!", $account.is_writable());
             return Err($error);
         }
     };
@@ -158,7 +397,10 @@ macro_rules! require_writable {
 #[macro_export]
 macro_rules! require_safe_add {
     ($left:expr, $right:expr, $error:expr) => {
-        $left.checked_add($right).ok_or($error)?
+        $left.checked_add($right).ok_or_else(|| {
+            $crate::__require_failed!("require_safe_add!", $left + $right);
+            $error
+        })?
     };
 }
 
@@ -166,7 +408,10 @@ macro_rules! require_safe_add {
 #[macro_export]
 macro_rules! require_safe_sub {
     ($left:expr, $right:expr, $error:expr) => {
-        $left.checked_sub($right).ok_or($error)?
+        $left.checked_sub($right).ok_or_else(|| {
+            $crate::__require_failed!("require_safe_sub!", $left - $right);
+            $error
+        })?
     };
 }
 
@@ -174,7 +419,10 @@ macro_rules! require_safe_sub {
 #[macro_export]
 macro_rules! require_safe_mul {
     ($left:expr, $right:expr, $error:expr) => {
-        $left.checked_mul($right).ok_or($error)?
+        $left.checked_mul($right).ok_or_else(|| {
+            $crate::__require_failed!("require_safe_mul!", $left * $right);
+            $error
+        })?
     };
 }
 
@@ -182,7 +430,10 @@ macro_rules! require_safe_mul {
 #[macro_export]
 macro_rules! require_safe_div {
     ($left:expr, $right:expr, $error:expr) => {
-        $left.checked_div($right).ok_or($error)?
+        $left.checked_div($right).ok_or_else(|| {
+            $crate::__require_failed!("require_safe_div!", $left / $right);
+            $error
+        })?
     };
 }
 
@@ -191,4 +442,4 @@ pub use {
     require, require_eq, require_neq, require_keys_eq, require_keys_neq,
     require_owned_by, require_signer, require_writable,
     require_safe_add, require_safe_sub, require_safe_mul, require_safe_div,
-}; 
\ No newline at end of file
+};
\ No newline at end of file
@@ -0,0 +1,318 @@
+//! IDL metadata emitted by the Pino macros.
+//!
+//! Only available with the host-only `idl-build` feature. The macros generate
+//! `#[test]` functions that print one JSON fragment per instruction, account,
+//! type and error; `pino idl build` runs them and assembles the fragments
+//! into an Anchor-compatible IDL.
+
+extern crate std;
+
+use serde::Serialize;
+use std::{boxed::Box, println, string::String};
+
+pub use std::vec::Vec;
+
+/// Prefix of the lines carrying IDL fragments in the test output.
+pub const IDL_MARKER: &str = "__pino_idl__:";
+
+/// Version of the Anchor IDL specification the fragments follow.
+pub const IDL_SPEC: &str = "0.1.0";
+
+/// Prints an IDL fragment of the given kind for `pino idl build`.
+pub fn print(kind: &str, value: &impl Serialize) {
+    let json = serde_json::to_string(value).expect("IDL fragments serialize to JSON");
+    println!("{}{} {}", IDL_MARKER, kind, json);
+}
+
+/// An instruction handler.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlInstruction {
+    /// Handler name.
+    pub name: String,
+    /// Bytes selecting the instruction.
+    pub discriminator: Vec<u8>,
+    /// Accounts, in the order the instruction expects them.
+    pub accounts: Vec<IdlInstructionAccount>,
+    /// Arguments following the discriminator.
+    pub args: Vec<IdlField>,
+}
+
+impl IdlInstruction {
+    /// Creates an instruction description.
+    pub fn new(
+        name: &str,
+        discriminator: &[u8],
+        accounts: Vec<IdlInstructionAccount>,
+        args: Vec<IdlField>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            discriminator: discriminator.into(),
+            accounts,
+            args,
+        }
+    }
+}
+
+/// An account expected by an instruction.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlInstructionAccount {
+    /// Field name in the Accounts struct.
+    pub name: String,
+    /// Whether the account must be writable.
+    #[serde(skip_serializing_if = "is_false")]
+    pub writable: bool,
+    /// Whether the account must sign.
+    #[serde(skip_serializing_if = "is_false")]
+    pub signer: bool,
+    /// Seeds of PDA accounts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pda: Option<IdlPda>,
+}
+
+impl IdlInstructionAccount {
+    /// Creates an account description.
+    pub fn new(name: &str, writable: bool, signer: bool, pda: Option<IdlPda>) -> Self {
+        Self {
+            name: name.into(),
+            writable,
+            signer,
+            pda,
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Seeds a PDA account is derived from.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlPda {
+    /// Seeds, in derivation order.
+    pub seeds: Vec<IdlSeed>,
+}
+
+impl IdlPda {
+    /// Creates a PDA description.
+    pub fn new(seeds: Vec<IdlSeed>) -> Self {
+        Self { seeds }
+    }
+}
+
+/// A single PDA seed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlSeed {
+    /// Constant bytes.
+    Const {
+        /// The seed bytes.
+        value: Vec<u8>,
+    },
+    /// The key of another account of the instruction.
+    Account {
+        /// Name of the account.
+        path: String,
+    },
+}
+
+impl IdlSeed {
+    /// A constant seed.
+    pub fn constant(value: &[u8]) -> Self {
+        IdlSeed::Const { value: value.into() }
+    }
+
+    /// A seed taken from the key of the named account.
+    pub fn account(path: &str) -> Self {
+        IdlSeed::Account { path: path.into() }
+    }
+}
+
+/// A named, typed field of an argument list or struct.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlField {
+    /// Field name.
+    pub name: String,
+    /// Field type.
+    #[serde(rename = "type")]
+    pub ty: IdlType,
+}
+
+impl IdlField {
+    /// Creates a field description.
+    pub fn new(name: &str, ty: IdlType) -> Self {
+        Self { name: name.into(), ty }
+    }
+}
+
+/// The type of a field.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdlType {
+    /// `bool`
+    Bool,
+    /// `u8`
+    U8,
+    /// `i8`
+    I8,
+    /// `u16`
+    U16,
+    /// `i16`
+    I16,
+    /// `u32`
+    U32,
+    /// `i32`
+    I32,
+    /// `u64`
+    U64,
+    /// `i64`
+    I64,
+    /// `u128`
+    U128,
+    /// `i128`
+    I128,
+    /// A 32-byte public key
+    Pubkey,
+    /// Length-prefixed UTF-8
+    String,
+    /// Length-prefixed bytes
+    Bytes,
+    /// A fixed-size array
+    Array(Box<IdlType>, usize),
+    /// A length-prefixed vector
+    Vec(Box<IdlType>),
+    /// An optional value
+    Option(Box<IdlType>),
+    /// A type described in the `types` section
+    Defined {
+        /// Name of the type.
+        name: String,
+    },
+}
+
+impl IdlType {
+    /// `[T; N]`
+    pub fn array(ty: IdlType, len: usize) -> Self {
+        IdlType::Array(Box::new(ty), len)
+    }
+
+    /// `Vec<T>`
+    pub fn vec(ty: IdlType) -> Self {
+        IdlType::Vec(Box::new(ty))
+    }
+
+    /// `Option<T>`
+    pub fn option(ty: IdlType) -> Self {
+        IdlType::Option(Box::new(ty))
+    }
+
+    /// A user-defined type.
+    pub fn defined(name: &str) -> Self {
+        IdlType::Defined { name: name.into() }
+    }
+}
+
+/// An account type, identified by its discriminator.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlAccount {
+    /// Type name.
+    pub name: String,
+    /// Bytes prefixing the account data.
+    pub discriminator: Vec<u8>,
+}
+
+impl IdlAccount {
+    /// Creates an account type description.
+    pub fn new(name: &str, discriminator: &[u8]) -> Self {
+        Self {
+            name: name.into(),
+            discriminator: discriminator.into(),
+        }
+    }
+}
+
+/// The layout of a user-defined type.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlTypeDef {
+    /// Type name.
+    pub name: String,
+    /// `bytemuck` for zero-copy account types; Borsh-compatible otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serialization: Option<String>,
+    /// Memory representation of zero-copy types.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repr: Option<IdlRepr>,
+    /// The fields.
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+impl IdlTypeDef {
+    /// A struct serialized field by field.
+    pub fn structure(name: &str, fields: Vec<IdlField>) -> Self {
+        Self {
+            name: name.into(),
+            serialization: None,
+            repr: None,
+            ty: IdlTypeDefTy::Struct { fields },
+        }
+    }
+
+    /// A `repr(C)` struct stored as its raw bytes.
+    pub fn zero_copy(name: &str, fields: Vec<IdlField>) -> Self {
+        Self {
+            serialization: Some("bytemuck".into()),
+            repr: Some(IdlRepr::C),
+            ..Self::structure(name, fields)
+        }
+    }
+}
+
+/// The kind of a user-defined type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlTypeDefTy {
+    /// A struct with named fields.
+    Struct {
+        /// The fields, in layout order.
+        fields: Vec<IdlField>,
+    },
+}
+
+/// Memory representation of a zero-copy type.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdlRepr {
+    /// `#[repr(C)]`
+    C,
+}
+
+/// A program error code.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdlErrorCode {
+    /// The code returned in `ProgramError::Custom`.
+    pub code: u32,
+    /// Variant name.
+    pub name: String,
+    /// Human-readable message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
+}
+
+impl IdlErrorCode {
+    /// Creates an error code description.
+    pub fn new(code: u32, name: &str, msg: Option<&str>) -> Self {
+        Self {
+            code,
+            name: name.into(),
+            msg: msg.map(Into::into),
+        }
+    }
+}
+
+/// Account descriptions of an Accounts struct, implemented by
+/// `#[derive(Accounts)]`.
+pub trait IdlAccounts {
+    /// Returns the accounts in the order the struct expects them.
+    fn idl_accounts() -> Vec<IdlInstructionAccount>;
+}
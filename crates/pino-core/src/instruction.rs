@@ -3,9 +3,6 @@
 //! This module provides structured instruction handling and Cross-Program
 //! Invocation (CPI) support built on Pinocchio's efficient syscalls.
 
-extern crate alloc;
-
-use alloc::vec::Vec;
 use pinocchio::{
     account_info::AccountInfo,
     instruction::{AccountMeta, Instruction, Signer},
@@ -13,7 +10,9 @@ use pinocchio::{
     ProgramResult,
 };
 use crate::{
+    collections::StackVec,
     context::CpiContext,
+    data::Vec,
     error::PinoError,
 };
 
@@ -24,6 +23,12 @@ pub use crate::context::InstructionData;
 pub trait ToInstructionData {
     /// Serializes the type to instruction data.
     fn to_instruction_data(&self) -> Result<Vec<u8>, PinoError>;
+
+    /// Serializes the type at the end of `buffer`, without allocating.
+    fn write_instruction_data<const N: usize>(
+        &self,
+        buffer: &mut StackVec<u8, N>,
+    ) -> Result<(), PinoError>;
 }
 
 /// Implements ToInstructionData for types that implement borsh::BorshSerialize.
@@ -35,6 +40,14 @@ where
         borsh::to_vec(self)
             .map_err(|_| PinoError::InvalidInstructionData)
     }
+
+    fn write_instruction_data<const N: usize>(
+        &self,
+        buffer: &mut StackVec<u8, N>,
+    ) -> Result<(), PinoError> {
+        borsh::BorshSerialize::serialize(self, buffer)
+            .map_err(|_| PinoError::InvalidInstructionData)
+    }
 }
 
 /// Performs a Cross-Program Invocation (CPI) using slice invoke.
@@ -73,57 +86,70 @@ pub fn create_instruction<'a>(
 }
 
 /// Builder for creating instructions with a fluent API.
-pub struct InstructionBuilder<'a> {
+///
+/// Accounts and data are kept on the stack, up to `ACCOUNTS` metas and `DATA`
+/// bytes, so the builder works without a heap. Accounts or raw data past
+/// those capacities are left out, and `invoke` and `invoke_signed` then fail
+/// with [`PinoError::StackVecFull`].
+pub struct InstructionBuilder<'a, const ACCOUNTS: usize, const DATA: usize> {
     program_id: &'a Pubkey,
-    accounts: Vec<AccountMeta<'a>>,
-    data: Vec<u8>,
+    accounts: StackVec<AccountMeta<'a>, ACCOUNTS>,
+    data: StackVec<u8, DATA>,
+    overflowed: bool,
 }
 
-impl<'a> InstructionBuilder<'a> {
+impl<'a, const ACCOUNTS: usize, const DATA: usize> InstructionBuilder<'a, ACCOUNTS, DATA> {
     /// Creates a new instruction builder.
     pub fn new(program_id: &'a Pubkey) -> Self {
         Self {
             program_id,
-            accounts: Vec::new(),
-            data: Vec::new(),
+            accounts: StackVec::new(),
+            data: StackVec::new(),
+            overflowed: false,
         }
     }
 
     /// Adds an account to the instruction.
     pub fn account(mut self, account: AccountMeta<'a>) -> Self {
-        self.accounts.push(account);
+        self.overflowed |= self.accounts.push(account).is_err();
         self
     }
 
     /// Adds multiple accounts to the instruction.
-    pub fn accounts(mut self, accounts: Vec<AccountMeta<'a>>) -> Self {
-        self.accounts.extend(accounts);
+    pub fn accounts(mut self, accounts: &[AccountMeta<'a>]) -> Self {
+        self.overflowed |= self.accounts.extend_from_slice(accounts).is_err();
         self
     }
 
-    /// Sets the instruction data.
+    /// Sets the instruction data, serialized in place.
     pub fn data<T: ToInstructionData>(mut self, data: T) -> Result<Self, PinoError> {
-        self.data = data.to_instruction_data()?;
+        self.data.clear();
+        data.write_instruction_data(&mut self.data)?;
         Ok(self)
     }
 
     /// Sets the instruction data from raw bytes.
-    pub fn data_raw(mut self, data: Vec<u8>) -> Self {
-        self.data = data;
+    pub fn data_raw(mut self, data: &[u8]) -> Self {
+        self.data.clear();
+        self.overflowed |= self.data.extend_from_slice(data).is_err();
         self
     }
 
-    /// Builds the instruction.
+    /// Builds the instruction, without the accounts or data that did not
+    /// fit.
     pub fn build(&'a self) -> Instruction<'a, 'a, 'a, 'a> {
         Instruction {
             program_id: self.program_id,
-            accounts: &self.accounts,
-            data: &self.data,
+            accounts: self.accounts.as_slice(),
+            data: self.data.as_slice(),
         }
     }
 
     /// Builds and invokes the instruction.
     pub fn invoke(&'a self, account_infos: &[&AccountInfo]) -> ProgramResult {
+        if self.overflowed {
+            return Err(PinoError::StackVecFull.into());
+        }
         let instruction = self.build();
         invoke(&instruction, account_infos)
     }
@@ -134,6 +160,9 @@ impl<'a> InstructionBuilder<'a> {
         account_infos: &[&AccountInfo],
         signer_seeds: &[Signer],
     ) -> ProgramResult {
+        if self.overflowed {
+            return Err(PinoError::StackVecFull.into());
+        }
         let instruction = self.build();
         invoke_signed(&instruction, account_infos, signer_seeds)
     }
@@ -169,42 +198,119 @@ pub mod system {
     use super::*;
     use crate::account::program_ids::SYSTEM_PROGRAM_ID;
 
-    /// Creates a system program transfer instruction with owned data.
-    pub fn transfer<'a>(from: &'a Pubkey, to: &'a Pubkey, lamports: u64) -> (Vec<AccountMeta<'a>>, Vec<u8>) {
-        let accounts = alloc::vec![
-            account_meta::writable_signer(from),
-            account_meta::writable(to),
-        ];
-
+    /// Creates a system program transfer instruction, ready to invoke.
+    pub fn transfer<'a>(from: &'a Pubkey, to: &'a Pubkey, lamports: u64) -> InstructionBuilder<'a, 2, 12> {
         // System program transfer instruction data
-        let mut data = Vec::with_capacity(12);
-        data.extend_from_slice(&2u32.to_le_bytes()); // Transfer instruction discriminator
-        data.extend_from_slice(&lamports.to_le_bytes());
-
-        (accounts, data)
+        let mut data = [0u8; 12];
+        data[..4].copy_from_slice(&2u32.to_le_bytes()); // Transfer instruction discriminator
+        data[4..].copy_from_slice(&lamports.to_le_bytes());
+
+        InstructionBuilder::new(&SYSTEM_PROGRAM_ID)
+            .account(account_meta::writable_signer(from))
+            .account(account_meta::writable(to))
+            .data_raw(&data)
     }
 
-    /// Creates a system program create account instruction with owned data.
+    /// Creates a system program create account instruction, ready to invoke.
     pub fn create_account<'a>(
         from: &'a Pubkey,
         to: &'a Pubkey,
         lamports: u64,
         space: u64,
         owner: &'a Pubkey,
-    ) -> (Vec<AccountMeta<'a>>, Vec<u8>) {
-        let accounts = alloc::vec![
-            account_meta::writable_signer(from),
-            account_meta::writable_signer(to),
-        ];
-
+    ) -> InstructionBuilder<'a, 2, 52> {
         // System program create account instruction data
-        let mut data = Vec::with_capacity(52);
-        data.extend_from_slice(&0u32.to_le_bytes()); // CreateAccount instruction discriminator
-        data.extend_from_slice(&lamports.to_le_bytes());
-        data.extend_from_slice(&space.to_le_bytes());
-        data.extend_from_slice(owner.as_ref());
+        let mut data = [0u8; 52];
+        data[..4].copy_from_slice(&0u32.to_le_bytes()); // CreateAccount instruction discriminator
+        data[4..12].copy_from_slice(&lamports.to_le_bytes());
+        data[12..20].copy_from_slice(&space.to_le_bytes());
+        data[20..].copy_from_slice(owner.as_ref());
+
+        InstructionBuilder::new(&SYSTEM_PROGRAM_ID)
+            .account(account_meta::writable_signer(from))
+            .account(account_meta::writable_signer(to))
+            .data_raw(&data)
+    }
+
+    /// Creates `account` with `space` zeroed bytes owned by `owner`.
+    ///
+    /// The payer funds the account with the rent-exempt minimum. PDA
+    /// accounts are created by passing their seeds in `signers`.
+    pub fn init_account(
+        payer: &AccountInfo,
+        account: &AccountInfo,
+        space: usize,
+        owner: &Pubkey,
+        signers: &[Signer],
+    ) -> ProgramResult {
+        use pinocchio::sysvars::{rent::Rent, Sysvar};
+
+        let required = Rent::get()
+            .map_err(|_| PinoError::SysvarUnavailable)?
+            .minimum_balance(space);
+        let current = account.lamports();
+
+        if current == 0 {
+            let mut data = [0u8; 52];
+            data[..4].copy_from_slice(&0u32.to_le_bytes()); // CreateAccount
+            data[4..12].copy_from_slice(&required.to_le_bytes());
+            data[12..20].copy_from_slice(&(space as u64).to_le_bytes());
+            data[20..].copy_from_slice(owner.as_ref());
+
+            return invoke_system(
+                &[
+                    AccountMeta::writable_signer(payer.key()),
+                    AccountMeta::writable_signer(account.key()),
+                ],
+                &data,
+                &[payer, account],
+                signers,
+            );
+        }
 
-        (accounts, data)
+        // `CreateAccount` fails on an account that already holds lamports,
+        // which anyone can send to the address beforehand; such an account
+        // is topped up, allocated and assigned instead.
+        if required > current {
+            invoke_transfer(payer, account, required - current)?;
+        }
+
+        let mut data = [0u8; 12];
+        data[..4].copy_from_slice(&8u32.to_le_bytes()); // Allocate
+        data[4..].copy_from_slice(&(space as u64).to_le_bytes());
+        invoke_system(&[AccountMeta::writable_signer(account.key())], &data, &[account], signers)?;
+
+        let mut data = [0u8; 36];
+        data[..4].copy_from_slice(&1u32.to_le_bytes()); // Assign
+        data[4..].copy_from_slice(owner.as_ref());
+        invoke_system(&[AccountMeta::writable_signer(account.key())], &data, &[account], signers)
+    }
+
+    /// Resizes `account` to `new_len` bytes, keeping it rent-exempt.
+    ///
+    /// Missing rent is transferred from `payer` through the System program;
+    /// rent no longer needed is refunded to `payer` directly, as the program
+    /// owns `account`.
+    pub fn realloc_account(
+        account: &AccountInfo,
+        payer: &AccountInfo,
+        new_len: usize,
+        zero: bool,
+    ) -> ProgramResult {
+        use pinocchio::sysvars::{rent::Rent, Sysvar};
+
+        let required = Rent::get()
+            .map_err(|_| PinoError::SysvarUnavailable)?
+            .minimum_balance(new_len);
+        let current = account.lamports();
+
+        if required > current {
+            invoke_transfer(payer, account, required - current)?;
+        } else if current > required {
+            crate::program::patterns::transfer_lamports(account, payer, current - required)?;
+        }
+
+        account.realloc(new_len, zero)
     }
 
     /// Creates a system program transfer instruction.
@@ -238,20 +344,50 @@ pub mod system {
             data,
         }
     }
+
+    /// Transfers `lamports` from the signer `from` through the System
+    /// program.
+    fn invoke_transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+        let mut data = [0u8; 12];
+        data[..4].copy_from_slice(&2u32.to_le_bytes()); // Transfer
+        data[4..].copy_from_slice(&lamports.to_le_bytes());
+
+        invoke_system(
+            &[AccountMeta::writable_signer(from.key()), AccountMeta::writable(to.key())],
+            &data,
+            &[from, to],
+            &[],
+        )
+    }
+
+    /// Invokes the System program with an instruction built on the stack.
+    fn invoke_system(
+        accounts: &[AccountMeta],
+        data: &[u8],
+        account_infos: &[&AccountInfo],
+        signers: &[Signer],
+    ) -> ProgramResult {
+        let instruction = Instruction {
+            program_id: &SYSTEM_PROGRAM_ID,
+            accounts,
+            data,
+        };
+        invoke_signed(&instruction, account_infos, signers)
+    }
 }
 
 /// Macro to simplify CPI calls.
+///
+/// `cpi!(ctx, data)` invokes the program of a [`CpiContext`] with `data`,
+/// signing with its signer seeds; `cpi!(ctx, data, signer_seeds)` signs with
+/// `signer_seeds` instead.
 #[macro_export]
 macro_rules! cpi {
-    ($ctx:expr, $instruction:expr) => {
-        $crate::instruction::invoke(&$instruction, &$ctx.to_account_infos())
+    ($ctx:expr, $data:expr) => {
+        $crate::context::CpiContext::invoke(&$ctx, &$data)
     };
-    ($ctx:expr, $instruction:expr, $signer_seeds:expr) => {
-        $crate::instruction::invoke_signed(
-            &$instruction,
-            &$ctx.to_account_infos(),
-            $signer_seeds,
-        )
+    ($ctx:expr, $data:expr, $signer_seeds:expr) => {
+        $crate::context::CpiContext::invoke(&$ctx.with_signer($signer_seeds), &$data)
     };
 }
 
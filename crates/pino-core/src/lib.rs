@@ -13,7 +13,7 @@
 //! ## Quick Start
 //!
 //! ```ignore
-//! use pino::prelude::*;
+//! use pino_core::prelude::*;
 //!
 //! #[pino_program]
 //! pub mod my_program {
@@ -44,9 +44,11 @@ pub use pinocchio;
 // Core framework modules
 pub mod account;
 pub mod context;
+pub mod data;
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
+pub mod panic;
 pub mod program;
 
 // Memory and performance optimizations
@@ -56,6 +58,10 @@ pub mod memory;
 // Utilities
 pub mod utils;
 
+// IDL metadata (host-only)
+#[cfg(feature = "idl-build")]
+pub mod idl;
+
 // Convenient prelude
 pub mod prelude;
 
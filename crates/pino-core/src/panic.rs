@@ -0,0 +1,102 @@
+//! Panic reporting without heap allocation.
+//!
+//! `pino_entrypoint!` installs [`log_panic`] with `panic_handler: pino`, or
+//! `panic_handler: pino_nostd` for `no_std` programs, and `#[pino_program]`
+//! with `panic_handler = pino`.
+
+use core::panic::PanicInfo;
+
+#[cfg(not(feature = "strip-panic-logs"))]
+use core::fmt::{self, Write};
+
+#[cfg(not(feature = "strip-panic-logs"))]
+use crate::collections::StackString;
+
+/// Longest panic log line; longer messages are cut.
+#[cfg(not(feature = "strip-panic-logs"))]
+const LOG_LEN: usize = 256;
+
+/// Writes into a `StackString`, cutting at a character boundary what does
+/// not fit.
+#[cfg(not(feature = "strip-panic-logs"))]
+struct Truncate<'a, const N: usize>(&'a mut StackString<N>);
+
+#[cfg(not(feature = "strip-panic-logs"))]
+impl<const N: usize> Write for Truncate<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = s.len().min(N - self.0.len());
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        let _ = self.0.push_str(&s[..end]);
+
+        if end == s.len() {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+/// Logs a panic with the compute units left when it happened:
+///
+/// `panicked at src/lib.rs:42:9 with 1234 CU left: <message>`
+///
+/// With the `strip-panic-logs` feature the line is a fixed `panicked`, so
+/// that no formatting code ends up in the program.
+#[cold]
+#[allow(unused_variables)]
+pub fn log_panic(info: &PanicInfo<'_>) {
+    #[cfg(not(feature = "strip-panic-logs"))]
+    {
+        // Read first, so that building the line is not counted.
+        let remaining = remaining_compute_units();
+
+        let mut line = StackString::<LOG_LEN>::new();
+        let _ = line.push_str("panicked");
+        if let Some(location) = info.location() {
+            let _ = line.push_str(" at ");
+            let _ = line.push_str(location.file());
+            let _ = line.push(':');
+            let _ = line.push_number(location.line() as u64);
+            let _ = line.push(':');
+            let _ = line.push_number(location.column() as u64);
+        }
+        let _ = line.push_str(" with ");
+        let _ = line.push_number(remaining);
+        let _ = line.push_str(" CU left: ");
+
+        // `PanicInfo::message` needs a newer compiler than the MSRV, so the
+        // message is taken from the `Display` output.
+        let mut text = StackString::<LOG_LEN>::new();
+        let _ = write!(Truncate(&mut text), "{}", info);
+        let _ = Truncate(&mut line).write_str(panic_message(text.as_str()));
+        pinocchio::log::sol_log(line.as_str());
+    }
+
+    #[cfg(feature = "strip-panic-logs")]
+    pinocchio::log::sol_log("panicked");
+}
+
+/// Returns the message of a panic from the `Display` output of its
+/// `PanicInfo`, which is `panicked at '<message>', <location>` before Rust
+/// 1.73 and `panicked at <location>:\n<message>` since.
+#[cfg(not(feature = "strip-panic-logs"))]
+fn panic_message(text: &str) -> &str {
+    if let Some(rest) = text.strip_prefix("panicked at '") {
+        return rest.rsplit_once("', ").map_or(rest, |(message, _)| message);
+    }
+    text.split_once(":\n").map_or("", |(_, message)| message)
+}
+
+/// Returns the compute units left in the transaction, or 0 off-chain.
+#[cfg(not(feature = "strip-panic-logs"))]
+fn remaining_compute_units() -> u64 {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        pinocchio::syscalls::sol_remaining_compute_units()
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    0
+}
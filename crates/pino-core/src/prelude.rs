@@ -20,12 +20,17 @@ pub use pinocchio::{
 pub use crate::{
     account::*,
     context::*,
+    data::PinoData,
     error::*,
     instruction::*,
     program::*,
     Result,
 };
 
+// `account::Program` (the wrapper) and `program::Program` (the ID trait)
+// share a name; the wrapper is the one used in Accounts structs.
+pub use crate::account::Program;
+
 // Re-export useful collections and memory utilities
 pub use crate::{
     collections::*,
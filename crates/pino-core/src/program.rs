@@ -26,7 +26,7 @@ pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8
 /// Returns an error if the derived address is not a valid PDA.
 pub fn create_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<Pubkey, PinoError> {
     pubkey::create_program_address(seeds, program_id)
-        .map_err(|_| PinoError::InvalidProgramId)
+        .map_err(|_| PinoError::InvalidSeeds)
 }
 
 /// Validates that a given address is a valid PDA for the given seeds.
@@ -38,7 +38,7 @@ pub fn validate_pda(
     let (expected_address, bump) = find_program_address(seeds, program_id);
     
     if address != &expected_address {
-        return Err(PinoError::InvalidProgramId);
+        return Err(PinoError::InvalidSeeds);
     }
     
     Ok(bump)
@@ -111,6 +111,36 @@ pub mod patterns {
 
         Ok(())
     }
+
+    /// Closes an account by moving all of its lamports to `destination`,
+    /// zeroing its data and handing it back to the System program.
+    pub fn close_account(
+        account: &AccountInfo,
+        destination: &AccountInfo,
+    ) -> Result<(), PinoError> {
+        require!(account.is_writable(), PinoError::AccountNotMutable);
+        require!(destination.is_writable(), PinoError::AccountNotMutable);
+
+        let lamports = account.lamports();
+        let destination_lamports = destination
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(PinoError::ArithmeticOverflow)?;
+
+        *destination.try_borrow_mut_lamports()
+            .map_err(|_| PinoError::AccountBorrowFailed)? = destination_lamports;
+        *account.try_borrow_mut_lamports()
+            .map_err(|_| PinoError::AccountBorrowFailed)? = 0;
+
+        account.try_borrow_mut_data()
+            .map_err(|_| PinoError::AccountBorrowFailed)?
+            .fill(0);
+
+        // SAFETY: no data borrows of `account` are held past this point.
+        unsafe { account.assign(&crate::account::program_ids::SYSTEM_PROGRAM_ID) };
+
+        Ok(())
+    }
 }
 
 /// Macro to declare a program ID.
@@ -124,26 +154,14 @@ macro_rules! declare_id {
         pub fn id() -> pinocchio::pubkey::Pubkey {
             ID
         }
-    };
-}
 
-/// Macro to generate a program module with automatic ID management.
-#[macro_export]
-macro_rules! pino_program {
-    (
-        $(#[$attr:meta])*
-        pub mod $name:ident {
-            $($item:item)*
-        }
-    ) => {
-        $(#[$attr])*
-        pub mod $name {
-            use $crate::prelude::*;
-            
-            $($item)*
+        #[cfg(all(test, feature = "idl-build"))]
+        #[test]
+        fn __pino_idl_print_address() {
+            $crate::idl::print("address", &$id);
         }
     };
 }
 
 // Re-export macros
-pub use {declare_id, pino_program}; 
\ No newline at end of file
+pub use declare_id; 
\ No newline at end of file
@@ -83,7 +83,7 @@ pub mod time {
     pub fn current_timestamp() -> Result<i64, PinoError> {
         // This would require accessing the Clock sysvar
         // For now, return an error indicating it's not implemented
-        Err(PinoError::Custom(0x2001))
+        Err(PinoError::SysvarUnavailable)
     }
 
     /// Checks if a timestamp is in the past.
@@ -151,7 +151,7 @@ pub mod validation {
     /// Validates that a number is within a range.
     pub fn validate_range(value: u64, min: u64, max: u64) -> Result<(), PinoError> {
         if value < min || value > max {
-            return Err(PinoError::Custom(0x2002));
+            return Err(PinoError::ValueOutOfRange);
         }
         Ok(())
     }
@@ -159,7 +159,7 @@ pub mod validation {
     /// Validates that a slice is not empty.
     pub fn validate_not_empty<T>(slice: &[T]) -> Result<(), PinoError> {
         if slice.is_empty() {
-            return Err(PinoError::Custom(0x2003));
+            return Err(PinoError::EmptyInput);
         }
         Ok(())
     }
@@ -168,7 +168,7 @@ pub mod validation {
     pub fn validate_ascii(bytes: &[u8]) -> Result<(), PinoError> {
         for &byte in bytes {
             if !byte.is_ascii() {
-                return Err(PinoError::Custom(0x2004));
+                return Err(PinoError::NonAsciiString);
             }
         }
         Ok(())
@@ -0,0 +1,27 @@
+//! `has_one` compiles against every account wrapper a target can be.
+
+use pino_core::prelude::*;
+
+pub const ID: Pubkey = [7; 32];
+
+#[pino_account]
+#[derive(Pod, Zeroable, Clone, Copy)]
+#[repr(C)]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(has_one = authority, has_one = delegate)]
+    pub vault: Account<'info, Vault>,
+    pub authority: UncheckedAccount<'info>,
+    pub delegate: AccountLoader<'info, Vault>,
+}
+
+#[test]
+fn has_one_accepts_unchecked_and_loader_targets() {
+    fn is_accounts<'info, T: Accounts<'info>>() {}
+    is_accounts::<Withdraw>();
+}
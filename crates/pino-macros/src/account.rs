@@ -0,0 +1,118 @@
+//! Account data macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Expr, Meta, Result};
+
+use crate::instruction::sighash;
+
+/// Reads `owner = <expr>` from the attribute arguments.
+pub fn owner(args: &[Meta]) -> Result<Option<Expr>> {
+    let mut owner = None;
+
+    for arg in args {
+        match arg {
+            Meta::NameValue(name_value) if name_value.path.is_ident("owner") => {
+                owner = Some(name_value.value.clone());
+            }
+            _ => return Err(Error::new_spanned(arg, "expected `owner = <program id>`")),
+        }
+    }
+
+    Ok(owner)
+}
+
+/// Returns whether the type is declared `#[repr(C)]`.
+fn is_repr_c(input: &DeriveInput) -> Result<bool> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                // `align(N)` / `packed(N)`
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+/// Generates the `AccountData` implementation of an account type, owned by
+/// `owner` or else by the `crate::ID` of the program declaring it.
+///
+/// The `Pod` requirement is enforced by the `AccountData` supertrait; the
+/// layout is checked here so that a missing `repr(C)` or implicit padding
+/// is reported on the type itself.
+pub fn account_data_impl(input: &DeriveInput, owner: Option<Expr>) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "account types must be structs",
+        ));
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "account types cannot be generic",
+        ));
+    }
+
+    if !is_repr_c(input)? {
+        return Err(Error::new_spanned(
+            name,
+            "account types must be `#[repr(C)]`",
+        ));
+    }
+
+    let field_types = data.fields.iter().map(|field| &field.ty);
+    let padding_message = format!("`{}` contains padding", name);
+    let discriminator = sighash("account", &name.to_string());
+
+    let owner = owner.map_or_else(|| quote! { crate::ID }, |owner| quote! { #owner });
+
+    let type_name = name.to_string();
+    let idl_fields = crate::idl::fields(data.fields.iter().enumerate().map(|(index, field)| {
+        let name = field
+            .ident
+            .as_ref()
+            .map_or_else(|| index.to_string(), ToString::to_string);
+        (name, &field.ty)
+    }));
+    let idl = crate::idl::print_test(
+        &format!("account_{}", name),
+        quote! {
+            ::pino_core::idl::print(
+                "account",
+                &::pino_core::idl::IdlAccount::new(#type_name, &[#(#discriminator),*]),
+            );
+            ::pino_core::idl::print(
+                "type",
+                &::pino_core::idl::IdlTypeDef::zero_copy(#type_name, #idl_fields),
+            );
+        },
+    );
+
+    Ok(quote! {
+        const _: () = assert!(
+            ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#field_types>())*,
+            #padding_message,
+        );
+
+        impl ::pino_core::account::AccountData for #name {
+            const DISCRIMINATOR: [u8; ::pino_core::account::DISCRIMINATOR_SIZE] = [#(#discriminator),*];
+
+            fn owner() -> ::pino_core::Pubkey {
+                #owner
+            }
+        }
+
+        #idl
+    })
+}
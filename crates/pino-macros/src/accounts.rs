@@ -0,0 +1,945 @@
+//! Accounts derive macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, Lifetime, PathArguments,
+    Result, Token, Type,
+};
+
+/// The account wrapper a field is declared as.
+enum AccountKind {
+    /// `Account<'info, T>`
+    Account,
+    /// `Signer<'info>`
+    Signer,
+    /// `Program<'info, T>`
+    Program,
+    /// `UncheckedAccount<'info>`
+    Unchecked,
+    /// `AccountLoader<'info, T>`
+    Loader,
+    /// `&'info AccountInfo`
+    Info,
+}
+
+impl AccountKind {
+    fn from_type(ty: &Type) -> Result<Self> {
+        let (path, reference) = match ty {
+            Type::Path(type_path) => (&type_path.path, false),
+            Type::Reference(reference) => match &*reference.elem {
+                Type::Path(type_path) => (&type_path.path, true),
+                _ => return Err(Error::new_spanned(ty, "unsupported account type")),
+            },
+            _ => return Err(Error::new_spanned(ty, "unsupported account type")),
+        };
+
+        let segment = path
+            .segments
+            .last()
+            .ok_or_else(|| Error::new_spanned(ty, "unsupported account type"))?;
+
+        match (segment.ident.to_string().as_str(), reference) {
+            ("AccountInfo", true) => Ok(AccountKind::Info),
+            // Accounts are borrowed from the input, never owned by the struct.
+            ("AccountInfo", false) => Err(Error::new_spanned(
+                ty,
+                "`AccountInfo` fields must be borrowed as `&'info AccountInfo`",
+            )),
+            ("Account", false) => Ok(AccountKind::Account),
+            ("Signer", false) => Ok(AccountKind::Signer),
+            ("Program", false) => Ok(AccountKind::Program),
+            ("UncheckedAccount", false) => Ok(AccountKind::Unchecked),
+            ("AccountLoader", false) => Ok(AccountKind::Loader),
+            _ => Err(Error::new_spanned(
+                ty,
+                "expected one of `Account`, `Signer`, `Program`, `UncheckedAccount`, \
+                 `AccountLoader` or `&AccountInfo`",
+            )),
+        }
+    }
+}
+
+/// A single `#[account(...)]` constraint.
+enum Constraint {
+    Mut,
+    Signer,
+    Init,
+    Payer(Ident),
+    Space(Expr),
+    Seeds(Vec<Expr>),
+    Bump(Option<Expr>),
+    HasOne(Ident, Option<Expr>),
+    Address(Expr, Option<Expr>),
+    Owner(Expr, Option<Expr>),
+    Raw(Expr, Option<Expr>),
+    Close(Ident),
+    Realloc(Expr),
+    ReallocPayer(Ident),
+    ReallocZero(Expr),
+}
+
+/// Parses the optional `@ error` suffix of a constraint.
+fn parse_custom_error(input: ParseStream) -> Result<Option<Expr>> {
+    if input.peek(Token![@]) {
+        input.parse::<Token![@]>()?;
+        Ok(Some(input.parse()?))
+    } else {
+        Ok(None)
+    }
+}
+
+impl Parse for Constraint {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![mut]) {
+            input.parse::<Token![mut]>()?;
+            return Ok(Constraint::Mut);
+        }
+
+        let key: Ident = input.parse()?;
+        if key == "realloc" && input.peek(Token![::]) {
+            input.parse::<Token![::]>()?;
+            let option: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            return match option.to_string().as_str() {
+                "payer" => Ok(Constraint::ReallocPayer(input.parse()?)),
+                "zero" => Ok(Constraint::ReallocZero(input.parse()?)),
+                _ => Err(Error::new(
+                    option.span(),
+                    format!("unknown account constraint `realloc::{}`", option),
+                )),
+            };
+        }
+
+        match key.to_string().as_str() {
+            "signer" => Ok(Constraint::Signer),
+            "init" => Ok(Constraint::Init),
+            "payer" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Payer(input.parse()?))
+            }
+            "space" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Space(input.parse()?))
+            }
+            "seeds" => {
+                input.parse::<Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                let seeds = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                Ok(Constraint::Seeds(seeds.into_iter().collect()))
+            }
+            "bump" => {
+                if input.peek(Token![=]) {
+                    input.parse::<Token![=]>()?;
+                    Ok(Constraint::Bump(Some(input.parse()?)))
+                } else {
+                    Ok(Constraint::Bump(None))
+                }
+            }
+            "has_one" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::HasOne(input.parse()?, parse_custom_error(input)?))
+            }
+            "address" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Address(input.parse()?, parse_custom_error(input)?))
+            }
+            "owner" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Owner(input.parse()?, parse_custom_error(input)?))
+            }
+            "constraint" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Raw(input.parse()?, parse_custom_error(input)?))
+            }
+            "close" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Close(input.parse()?))
+            }
+            "realloc" => {
+                input.parse::<Token![=]>()?;
+                Ok(Constraint::Realloc(input.parse()?))
+            }
+            _ => Err(Error::new(key.span(), format!("unknown account constraint `{}`", key))),
+        }
+    }
+}
+
+/// The `init` constraint group.
+struct InitConstraint {
+    payer: Ident,
+    space: Expr,
+}
+
+/// The `seeds`/`bump` constraint group.
+struct SeedsConstraint {
+    seeds: Vec<Expr>,
+    /// The canonical bump is searched for when no bump expression is given.
+    bump: Option<Expr>,
+}
+
+/// The `realloc` constraint group.
+struct ReallocConstraint {
+    len: Expr,
+    payer: Ident,
+    zero: Expr,
+}
+
+/// A declarative check that runs once every field is bound.
+enum Check {
+    /// A Pubkey field of the account data must equal a sibling's key.
+    HasOne(Ident),
+    /// The account key must equal the given key.
+    Address(Expr),
+    /// The account must be owned by the given program.
+    Owner(Expr),
+    /// A free-form boolean expression.
+    Raw(Expr),
+}
+
+/// All constraints declared on a field.
+#[derive(Default)]
+struct Constraints {
+    mutable: bool,
+    signer: bool,
+    init: Option<InitConstraint>,
+    seeds: Option<SeedsConstraint>,
+    /// Checks paired with an optional custom error.
+    checks: Vec<(Check, Option<Expr>)>,
+    realloc: Option<ReallocConstraint>,
+    /// Receives the lamports when the account is closed on exit.
+    close: Option<Ident>,
+}
+
+impl Constraints {
+    fn from_attrs(field: &Ident, attrs: &[syn::Attribute]) -> Result<Self> {
+        let mut constraints = Constraints::default();
+        let mut init = false;
+        let mut payer = None;
+        let mut space = None;
+        let mut seeds = None;
+        let mut bump = None;
+        let mut realloc = None;
+        let mut realloc_payer = None;
+        let mut realloc_zero = None;
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("account")) {
+            let parsed =
+                attr.parse_args_with(Punctuated::<Constraint, Token![,]>::parse_terminated)?;
+            for constraint in parsed {
+                match constraint {
+                    Constraint::Mut => constraints.mutable = true,
+                    Constraint::Signer => constraints.signer = true,
+                    Constraint::Init => init = true,
+                    Constraint::Payer(ident) => payer = Some(ident),
+                    Constraint::Space(expr) => space = Some(expr),
+                    Constraint::Seeds(exprs) => seeds = Some(exprs),
+                    Constraint::Bump(expr) => bump = Some(expr),
+                    Constraint::HasOne(target, error) => {
+                        constraints.checks.push((Check::HasOne(target), error))
+                    }
+                    Constraint::Address(key, error) => {
+                        constraints.checks.push((Check::Address(key), error))
+                    }
+                    Constraint::Owner(owner, error) => {
+                        constraints.checks.push((Check::Owner(owner), error))
+                    }
+                    Constraint::Raw(expr, error) => {
+                        constraints.checks.push((Check::Raw(expr), error))
+                    }
+                    Constraint::Close(destination) => {
+                        // Closing drains and rewrites the account.
+                        constraints.mutable = true;
+                        constraints.close = Some(destination);
+                    }
+                    Constraint::Realloc(len) => realloc = Some(len),
+                    Constraint::ReallocPayer(ident) => realloc_payer = Some(ident),
+                    Constraint::ReallocZero(expr) => realloc_zero = Some(expr),
+                }
+            }
+        }
+
+        if init {
+            let payer = payer
+                .ok_or_else(|| Error::new(field.span(), "`init` requires `payer = <field>`"))?;
+            let space = space
+                .ok_or_else(|| Error::new(field.span(), "`init` requires `space = <expr>`"))?;
+            // The new account is always written to.
+            constraints.mutable = true;
+            constraints.init = Some(InitConstraint { payer, space });
+        } else if let Some(payer) = payer {
+            return Err(Error::new(payer.span(), "`payer` is only valid with `init`"));
+        } else if space.is_some() {
+            return Err(Error::new(field.span(), "`space` is only valid with `init`"));
+        }
+
+        match (seeds, bump) {
+            (Some(seeds), Some(bump)) => {
+                constraints.seeds = Some(SeedsConstraint { seeds, bump });
+            }
+            (Some(_), None) => {
+                return Err(Error::new(field.span(), "`seeds` requires `bump` or `bump = <expr>`"));
+            }
+            (None, Some(_)) => {
+                return Err(Error::new(field.span(), "`bump` is only valid with `seeds`"));
+            }
+            (None, None) => {}
+        }
+
+        if let Some(len) = realloc {
+            let payer = realloc_payer.ok_or_else(|| {
+                Error::new(field.span(), "`realloc` requires `realloc::payer = <field>`")
+            })?;
+            let zero = realloc_zero.ok_or_else(|| {
+                Error::new(field.span(), "`realloc` requires `realloc::zero = <bool>`")
+            })?;
+            constraints.mutable = true;
+            constraints.realloc = Some(ReallocConstraint { len, payer, zero });
+        } else if realloc_payer.is_some() || realloc_zero.is_some() {
+            return Err(Error::new(
+                field.span(),
+                "`realloc::payer` and `realloc::zero` are only valid with `realloc`",
+            ));
+        }
+
+        Ok(constraints)
+    }
+}
+
+/// A parsed field of an Accounts struct.
+struct AccountField {
+    ident: Ident,
+    ty: Type,
+    kind: AccountKind,
+    constraints: Constraints,
+}
+
+impl AccountField {
+    /// The identifier holding this field's raw `AccountInfo`.
+    fn info_ident(&self) -> Ident {
+        info_ident(&self.ident)
+    }
+
+    /// Whether this field is declared as `Program<'info, System>`.
+    fn is_system_program(&self) -> bool {
+        let Type::Path(type_path) = &self.ty else {
+            return false;
+        };
+        let Some(segment) = type_path.path.segments.last() else {
+            return false;
+        };
+        let PathArguments::AngleBracketed(generics) = &segment.arguments else {
+            return false;
+        };
+
+        matches!(self.kind, AccountKind::Program)
+            && generics.args.iter().any(|arg| match arg {
+                GenericArgument::Type(Type::Path(program)) => program
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "System"),
+                _ => false,
+            })
+    }
+
+    /// Takes this field's account off the cursor.
+    fn take_info(&self) -> TokenStream {
+        let info = self.info_ident();
+
+        quote! {
+            let #info = accounts.take()?;
+        }
+    }
+
+    /// Checks the flags of the raw account before anything touches it.
+    fn checks(&self) -> TokenStream {
+        let info = self.info_ident();
+
+        let mut checks = Vec::new();
+        if self.constraints.mutable {
+            checks.push(require(
+                quote! { #info.is_writable() },
+                quote! { ::pino_core::error::PinoError::AccountNotMutable },
+            ));
+        }
+        if self.constraints.signer {
+            checks.push(require(
+                quote! { #info.is_signer() },
+                quote! { ::pino_core::error::PinoError::AccountNotSigner },
+            ));
+        }
+        // The accounts this one is closed into or resized from are checked
+        // here, rather than failing at exit or inside the CPI.
+        if let Some(destination) = &self.constraints.close {
+            let destination = info_ident(destination);
+            checks.push(require(
+                quote! { #destination.is_writable() },
+                quote! { ::pino_core::error::PinoError::AccountNotMutable },
+            ));
+        }
+        if let Some(realloc) = &self.constraints.realloc {
+            let payer = info_ident(&realloc.payer);
+            checks.push(require(
+                quote! { #payer.is_signer() },
+                quote! { ::pino_core::error::PinoError::AccountNotSigner },
+            ));
+        }
+
+        quote! { #(#checks)* }
+    }
+
+    /// Verifies the PDA address of a `seeds` account and records its bump and
+    /// signer seeds.
+    fn pda(&self, lifetime: &Lifetime) -> TokenStream {
+        let Some(pda) = &self.constraints.seeds else {
+            return TokenStream::new();
+        };
+
+        let ident = &self.ident;
+        let info = self.info_ident();
+        let seeds_ident = seeds_ident(ident);
+        let bump_ident = bump_ident(ident);
+        let seeds = &pda.seeds;
+        let seed_count = seeds.len();
+
+        let bump = match &pda.bump {
+            Some(bump) => {
+                let indices = 0..seed_count;
+                let check = require(
+                    quote! { #info.key() == &__address },
+                    quote! { ::pino_core::error::PinoError::InvalidSeeds },
+                );
+                quote! {
+                    let #bump_ident: u8 = #bump;
+                    let __address = ::pino_core::program::create_program_address(
+                        &[#(#seeds_ident[#indices],)* &[#bump_ident]],
+                        program_id,
+                    )?;
+                    #check
+                }
+            }
+            None => quote! {
+                let #bump_ident = ::pino_core::program::validate_pda(
+                    #info.key(),
+                    &#seeds_ident,
+                    program_id,
+                )?;
+            },
+        };
+
+        // The seeds are kept for the whole instruction, so each one must
+        // borrow for its lifetime. Annotating them one by one makes the
+        // compiler reject any other seed at its own expression.
+        let seeds = seeds.iter().map(|seed| {
+            quote_spanned! {seed.span()=>
+                {
+                    let seed: &#lifetime [u8] = #seed;
+                    seed
+                }
+            }
+        });
+
+        let indices = 0..seed_count;
+        quote! {
+            let #seeds_ident: [&[u8]; #seed_count] = [#(#seeds),*];
+            #bump
+            bumps.#ident = #bump_ident;
+            signer_seeds.#ident = ::core::option::Option::Some((
+                #info.key(),
+                [
+                    #(::pino_core::pinocchio::instruction::Seed::from(#seeds_ident[#indices]),)*
+                    ::pino_core::pinocchio::instruction::Seed::from(
+                        ::pino_core::context::bump_seed(#bump_ident),
+                    ),
+                ],
+            ));
+        }
+    }
+
+    /// Creates the account through the System program when marked `init`.
+    ///
+    /// PDA accounts sign the creation with their seeds and bump.
+    fn init(&self) -> TokenStream {
+        let Some(init) = &self.constraints.init else {
+            return TokenStream::new();
+        };
+
+        let info = self.info_ident();
+        let payer = info_ident(&init.payer);
+        let space = &init.space;
+
+        let signers = match &self.constraints.seeds {
+            Some(pda) => {
+                let seeds_ident = seeds_ident(&self.ident);
+                let bump_ident = bump_ident(&self.ident);
+                let indices = 0..pda.seeds.len();
+                quote! {
+                    &[::pino_core::pinocchio::instruction::Signer::from(&[
+                        #(::pino_core::pinocchio::instruction::Seed::from(#seeds_ident[#indices]),)*
+                        ::pino_core::pinocchio::instruction::Seed::from(&[#bump_ident][..]),
+                    ][..])]
+                }
+            }
+            None => quote! { &[] },
+        };
+
+        quote! {
+            ::pino_core::instruction::system::init_account(
+                #payer,
+                #info,
+                (#space) as usize,
+                program_id,
+                #signers,
+            )?;
+        }
+    }
+
+    /// Resizes the account data when marked `realloc`.
+    fn realloc(&self) -> TokenStream {
+        let Some(realloc) = &self.constraints.realloc else {
+            return TokenStream::new();
+        };
+
+        let info = self.info_ident();
+        let payer = info_ident(&realloc.payer);
+        let len = &realloc.len;
+        let zero = &realloc.zero;
+
+        quote! {
+            ::pino_core::instruction::system::realloc_account(
+                #info,
+                #payer,
+                (#len) as usize,
+                #zero,
+            )?;
+        }
+    }
+
+    /// Closes the account into its `close` destination.
+    fn close(&self, fields: &[AccountField]) -> TokenStream {
+        let Some(destination) = &self.constraints.close else {
+            return TokenStream::new();
+        };
+
+        let account = self.self_info();
+        let destination = fields
+            .iter()
+            .find(|field| &field.ident == destination)
+            .expect("close destination is validated")
+            .self_info();
+
+        quote! {
+            ::pino_core::program::patterns::close_account(#account, #destination)?;
+        }
+    }
+
+    /// The `AccountInfo` of this field, reached through `self`.
+    fn self_info(&self) -> TokenStream {
+        let ident = &self.ident;
+        match self.kind {
+            AccountKind::Info => quote! { self.#ident },
+            _ => quote! { self.#ident.info() },
+        }
+    }
+
+    /// Runs the `has_one`, `address`, `owner` and `constraint` checks.
+    fn constraint_checks(&self) -> TokenStream {
+        let ident = &self.ident;
+        let info = self.info_ident();
+
+        let checks = self.constraints.checks.iter().map(|(check, error)| {
+            let (condition, default_error) = match check {
+                Check::HasOne(target) => (
+                    quote! {
+                        &#ident.load()?.#target
+                            == ::pino_core::account::ToAccountInfo::to_account_info(&#target).key()
+                    },
+                    quote! { ::pino_core::error::PinoError::ConstraintHasOne },
+                ),
+                Check::Address(key) => (
+                    quote! { #info.key() == &(#key) },
+                    quote! { ::pino_core::error::PinoError::ConstraintAddress },
+                ),
+                Check::Owner(owner) => (
+                    quote! { #info.is_owned_by(&(#owner)) },
+                    quote! { ::pino_core::error::PinoError::InvalidAccountOwner },
+                ),
+                Check::Raw(expr) => (
+                    quote! { #expr },
+                    quote! { ::pino_core::error::PinoError::ConstraintRaw },
+                ),
+            };
+            let error = match error {
+                Some(error) => quote! { #error },
+                None => default_error,
+            };
+
+            require(condition, error)
+        });
+
+        quote! { #(#checks)* }
+    }
+
+    /// Describes the account for the IDL.
+    fn idl_account(&self, fields: &[&Ident]) -> TokenStream {
+        let name = self.ident.to_string();
+        let writable = self.constraints.mutable;
+        // Accounts created without seeds sign their own creation.
+        let signer = self.constraints.signer
+            || matches!(self.kind, AccountKind::Signer)
+            || (self.constraints.init.is_some() && self.constraints.seeds.is_none());
+
+        let pda = self
+            .constraints
+            .seeds
+            .as_ref()
+            .and_then(|pda| {
+                pda.seeds
+                    .iter()
+                    .map(|seed| crate::idl::seed(seed, fields))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .map(|seeds| {
+                quote! {
+                    Some(::pino_core::idl::IdlPda::new(::pino_core::idl::Vec::from([#(#seeds),*])))
+                }
+            })
+            .unwrap_or_else(|| quote! { None });
+
+        quote! {
+            ::pino_core::idl::IdlInstructionAccount::new(#name, #writable, #signer, #pda)
+        }
+    }
+
+    /// Binds the validated typed wrapper to the field name.
+    ///
+    /// Accounts created by `init` do not carry a discriminator yet, so their
+    /// wrapper writes it instead of checking it.
+    fn construct(&self) -> TokenStream {
+        let ident = &self.ident;
+        let ty = &self.ty;
+        let info = self.info_ident();
+        let init = self.constraints.init.is_some();
+
+        let construct = match self.kind {
+            AccountKind::Account | AccountKind::Loader if init => {
+                quote! { <#ty>::try_initialize(#info)? }
+            }
+            AccountKind::Account => quote! { <#ty>::new(#info)? },
+            AccountKind::Signer => quote! { <#ty>::new(#info)? },
+            AccountKind::Program => quote! { <#ty>::try_from_info(#info)? },
+            AccountKind::Unchecked => quote! { <#ty>::new(#info) },
+            AccountKind::Loader => quote! { <#ty>::new(#info)? },
+            AccountKind::Info => quote! { #info },
+        };
+
+        quote! {
+            let #ident: #ty = #construct;
+        }
+    }
+}
+
+/// Generates a check that returns `error` when `condition` is false, so that
+/// the error is only built on failure.
+fn require(condition: TokenStream, error: TokenStream) -> TokenStream {
+    quote! {
+        if !(#condition) {
+            ::pino_core::__require_failed!("#[account]", #condition);
+            return ::core::result::Result::Err(::core::convert::Into::into(#error));
+        }
+    }
+}
+
+fn info_ident(field: &Ident) -> Ident {
+    quote::format_ident!("__{}_info", field)
+}
+
+fn seeds_ident(field: &Ident) -> Ident {
+    quote::format_ident!("__{}_seeds", field)
+}
+
+fn bump_ident(field: &Ident) -> Ident {
+    quote::format_ident!("__{}_bump", field)
+}
+
+/// Checks cross-field references made by constraints.
+fn validate_fields(fields: &[AccountField]) -> Result<()> {
+    let find = |ident: &Ident| fields.iter().find(|field| &field.ident == ident);
+
+    for field in fields {
+        for (check, _) in &field.constraints.checks {
+            let Check::HasOne(target) = check else {
+                continue;
+            };
+            if !matches!(field.kind, AccountKind::Account | AccountKind::Loader) {
+                return Err(Error::new(
+                    target.span(),
+                    "`has_one` requires an `Account` or `AccountLoader` field",
+                ));
+            }
+            if find(target).is_none() {
+                return Err(Error::new(
+                    target.span(),
+                    format!("`{}` is not a field of this struct", target),
+                ));
+            }
+        }
+
+        if let Some(destination) = &field.constraints.close {
+            if destination == &field.ident {
+                return Err(Error::new(
+                    destination.span(),
+                    "an account cannot be closed into itself",
+                ));
+            }
+            if find(destination).is_none() {
+                return Err(Error::new(
+                    destination.span(),
+                    format!("`{}` is not a field of this struct", destination),
+                ));
+            }
+        }
+
+        let payer = match (&field.constraints.init, &field.constraints.realloc) {
+            (Some(init), _) => &init.payer,
+            (None, Some(realloc)) => &realloc.payer,
+            (None, None) => continue,
+        };
+
+        if find(payer).is_none() {
+            return Err(Error::new(
+                payer.span(),
+                format!("payer `{}` is not a field of this struct", payer),
+            ));
+        }
+
+        if !fields.iter().any(AccountField::is_system_program) {
+            return Err(Error::new(
+                field.ident.span(),
+                "`init` and `realloc` require a `Program<'info, System>` field",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Implementation of the Accounts derive macro
+pub fn derive_accounts_impl(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "#[derive(Accounts)] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "#[derive(Accounts)] can only be used on structs",
+            ))
+        }
+    };
+
+    // Structs without accounts may omit the lifetime; the impl still needs one.
+    let mut impl_generics_source = input.generics.clone();
+    let info_lifetime = match input.generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime: syn::Lifetime = syn::parse_quote!('info);
+            impl_generics_source
+                .params
+                .insert(0, syn::GenericParam::Lifetime(syn::LifetimeParam::new(lifetime.clone())));
+            lifetime
+        }
+    };
+
+    let account_fields = fields
+        .iter()
+        .map(|field| {
+            Ok(AccountField {
+                ident: field.ident.clone().expect("named field"),
+                ty: field.ty.clone(),
+                kind: AccountKind::from_type(&field.ty)?,
+                constraints: Constraints::from_attrs(
+                    field.ident.as_ref().expect("named field"),
+                    &field.attrs,
+                )?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    validate_fields(&account_fields)?;
+
+    let (init_fields, existing_fields): (Vec<_>, Vec<_>) = account_fields
+        .iter()
+        .partition(|field| field.constraints.init.is_some());
+
+    // Existing accounts are bound first so that the seeds of accounts being
+    // created can refer to them; PDA checks on existing accounts run once
+    // every field is available. Accounts are only resized once every check
+    // has passed.
+    let take_infos = account_fields.iter().map(AccountField::take_info);
+    let checks = account_fields.iter().map(AccountField::checks);
+    let constructs = existing_fields.iter().map(|field| field.construct());
+    let inits = init_fields.iter().map(|field| {
+        let pda = field.pda(&info_lifetime);
+        let init = field.init();
+        let construct = field.construct();
+        quote! { #pda #init #construct }
+    });
+    let pdas = existing_fields.iter().map(|field| field.pda(&info_lifetime));
+    let reallocs = account_fields.iter().map(AccountField::realloc);
+    let constraint_checks = account_fields.iter().map(AccountField::constraint_checks);
+    let closes = account_fields
+        .iter()
+        .map(|field| field.close(&account_fields))
+        .collect::<Vec<_>>();
+
+    let exit = if account_fields.iter().any(|field| field.constraints.close.is_some()) {
+        quote! {
+            fn exit(&self, _program_id: &::pino_core::Pubkey) -> ::pino_core::ProgramResult {
+                #(#closes)*
+                Ok(())
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+    let len = account_fields.len();
+    let field_names = account_fields.iter().map(|field| &field.ident);
+    let field_idents = account_fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let idl_accounts = account_fields
+        .iter()
+        .map(|field| field.idl_account(&field_idents));
+
+    let vis = &input.vis;
+    let bumps_name = quote::format_ident!("{}Bumps", name);
+    let bumps_doc = format!("PDA bump seeds found while validating [`{}`].", name);
+    let bump_fields = account_fields
+        .iter()
+        .filter(|field| field.constraints.seeds.is_some())
+        .map(|field| {
+            let ident = &field.ident;
+            let doc = format!("Bump seed of `{}`.", ident);
+            quote! {
+                #[doc = #doc]
+                pub #ident: u8
+            }
+        });
+
+    // Signer seeds borrow from the accounts, so their struct takes the
+    // lifetime of the context; structs without PDAs use `()`.
+    let pda_fields = account_fields
+        .iter()
+        .filter_map(|field| {
+            let pda = field.constraints.seeds.as_ref()?;
+            Some((&field.ident, pda.seeds.len() + 1))
+        })
+        .collect::<Vec<_>>();
+    let seeds_name = quote::format_ident!("{}SignerSeeds", name);
+    let (signer_seeds_ty, signer_seeds_struct) = if pda_fields.is_empty() {
+        (quote! { () }, TokenStream::new())
+    } else {
+        let seeds_doc = format!("Signer seeds of the PDA accounts validated in [`{}`].", name);
+        let idents = pda_fields.iter().map(|(ident, _)| ident).collect::<Vec<_>>();
+        let seed_fields = pda_fields.iter().map(|(ident, count)| {
+            quote! {
+                #ident: ::core::option::Option<(
+                    &'info ::pino_core::Pubkey,
+                    [::pino_core::pinocchio::instruction::Seed<'info>; #count],
+                )>
+            }
+        });
+        (
+            quote! { #seeds_name<'__info> },
+            quote! {
+                #[doc = #seeds_doc]
+                #[derive(Default)]
+                #vis struct #seeds_name<'info> {
+                    #(#seed_fields),*
+                }
+
+                impl<'info> ::pino_core::context::SignerSeeds<'info> for #seeds_name<'info> {
+                    fn signer_for(
+                        &self,
+                        address: &::pino_core::Pubkey,
+                    ) -> ::core::option::Option<::pino_core::pinocchio::instruction::Signer<'info, '_>> {
+                        #(
+                            if let ::core::option::Option::Some((key, seeds)) = &self.#idents {
+                                if *key == address {
+                                    return ::core::option::Option::Some(
+                                        ::pino_core::pinocchio::instruction::Signer::from(&seeds[..]),
+                                    );
+                                }
+                            }
+                        )*
+                        ::core::option::Option::None
+                    }
+                }
+            },
+        )
+    };
+
+    let (impl_generics, _, _) = impl_generics_source.split_for_impl();
+    let (plain_impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[doc = #bumps_doc]
+        #[derive(Debug, Default, Clone, Copy)]
+        #vis struct #bumps_name {
+            #(#bump_fields),*
+        }
+
+        #signer_seeds_struct
+
+        impl #plain_impl_generics ::pino_core::context::Bumps for #name #ty_generics #where_clause {
+            type Bumps = #bumps_name;
+            type SignerSeeds<'__info> = #signer_seeds_ty;
+        }
+
+        impl #impl_generics ::pino_core::context::Accounts<#info_lifetime> for #name #ty_generics #where_clause {
+            type Slots = ::pino_core::context::AccountSlots<#len>;
+
+            #[allow(unused_variables)]
+            fn try_accounts(
+                program_id: &::pino_core::Pubkey,
+                accounts: &mut ::pino_core::context::RemainingAccounts<#info_lifetime>,
+                instruction_data: &#info_lifetime [u8],
+                bumps: &mut Self::Bumps,
+                signer_seeds: &mut Self::SignerSeeds<#info_lifetime>,
+            ) -> ::core::result::Result<Self, ::pino_core::pinocchio::program_error::ProgramError> {
+                #(#take_infos)*
+                #(#checks)*
+                #(#constructs)*
+                #(#inits)*
+                #(#pdas)*
+                #(#constraint_checks)*
+                #(#reallocs)*
+
+                Ok(Self {
+                    #(#field_names),*
+                })
+            }
+
+            #exit
+        }
+
+        #[cfg(feature = "idl-build")]
+        impl #plain_impl_generics ::pino_core::idl::IdlAccounts for #name #ty_generics #where_clause {
+            fn idl_accounts() -> ::pino_core::idl::Vec<::pino_core::idl::IdlInstructionAccount> {
+                ::pino_core::idl::Vec::from([#(#idl_accounts),*])
+            }
+        }
+    })
+}
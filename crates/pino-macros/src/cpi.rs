@@ -0,0 +1,127 @@
+//! CpiAccounts derive macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Error, Fields, Ident, Result};
+
+/// An account passed to the invoked program.
+struct CpiField {
+    ident: Ident,
+    writable: bool,
+    signer: bool,
+}
+
+impl CpiField {
+    /// Reads `#[cpi(mut)]` and `#[cpi(signer)]`.
+    fn from_attrs(ident: Ident, attrs: &[Attribute]) -> Result<Self> {
+        let mut field = CpiField {
+            ident,
+            writable: false,
+            signer: false,
+        };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("cpi")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("mut") {
+                    field.writable = true;
+                } else if meta.path.is_ident("signer") {
+                    field.signer = true;
+                } else {
+                    return Err(meta.error("expected `mut` or `signer`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(field)
+    }
+
+    fn info(&self) -> TokenStream {
+        let ident = &self.ident;
+        quote! { ::pino_core::account::ToAccountInfo::to_account_info(&self.#ident) }
+    }
+}
+
+/// Implementation of the CpiAccounts derive macro
+pub fn derive_cpi_accounts_impl(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "#[derive(CpiAccounts)] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "#[derive(CpiAccounts)] can only be used on structs",
+            ))
+        }
+    };
+
+    let Some(info_lifetime) = input.generics.lifetimes().next().map(|param| &param.lifetime)
+    else {
+        return Err(Error::new_spanned(
+            name,
+            "#[derive(CpiAccounts)] requires a lifetime for the accounts",
+        ));
+    };
+
+    let cpi_fields = fields
+        .iter()
+        .map(|field| {
+            CpiField::from_attrs(field.ident.clone().expect("named field"), &field.attrs)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Metas and infos are pushed in field order, which is the order the
+    // invoked program expects its accounts in.
+    let push_metas = cpi_fields.iter().map(|field| {
+        let info = field.info();
+        let writable = field.writable;
+        let signer = field.signer;
+        quote! {
+            metas.push(::pino_core::pinocchio::instruction::AccountMeta::new(
+                #info.key(),
+                #writable,
+                #signer,
+            ))?;
+        }
+    });
+    let push_infos = cpi_fields.iter().map(|field| {
+        let info = field.info();
+        quote! { infos.push(#info)?; }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::pino_core::context::CpiAccounts<#info_lifetime> for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn to_account_metas<const __N: usize>(
+                &self,
+                metas: &mut ::pino_core::collections::StackVec<
+                    ::pino_core::pinocchio::instruction::AccountMeta<#info_lifetime>,
+                    __N,
+                >,
+            ) -> ::core::result::Result<(), ::pino_core::error::PinoError> {
+                #(#push_metas)*
+                Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn to_account_infos<const __N: usize>(
+                &self,
+                infos: &mut ::pino_core::collections::StackVec<&#info_lifetime ::pino_core::AccountInfo, __N>,
+            ) -> ::core::result::Result<(), ::pino_core::error::PinoError> {
+                #(#push_infos)*
+                Ok(())
+            }
+        }
+    })
+}
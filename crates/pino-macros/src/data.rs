@@ -0,0 +1,192 @@
+//! PinoData derive macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Ident, Result, Type};
+
+/// How a field is laid out on the wire.
+enum FieldKind {
+    /// A `Pod` value stored as its raw bytes.
+    Fixed,
+    /// `&[u8]` with a `u32` length prefix.
+    Bytes,
+    /// `&str` with a `u32` length prefix.
+    Str,
+}
+
+impl FieldKind {
+    fn from_type(ty: &Type) -> Self {
+        let Type::Reference(reference) = ty else {
+            return FieldKind::Fixed;
+        };
+
+        match &*reference.elem {
+            Type::Slice(slice) if is_ident(&slice.elem, "u8") => FieldKind::Bytes,
+            elem if is_ident(elem, "str") => FieldKind::Str,
+            _ => FieldKind::Fixed,
+        }
+    }
+}
+
+fn is_ident(ty: &Type, name: &str) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident(name))
+}
+
+struct DataField {
+    ident: Ident,
+    ty: Type,
+    kind: FieldKind,
+}
+
+impl DataField {
+    fn read(&self) -> TokenStream {
+        let ident = &self.ident;
+        let ty = &self.ty;
+        match self.kind {
+            FieldKind::Fixed => quote! { let #ident = __reader.read::<#ty>()?; },
+            FieldKind::Bytes => quote! { let #ident = __reader.read_bytes()?; },
+            FieldKind::Str => quote! { let #ident = __reader.read_str()?; },
+        }
+    }
+
+    fn len(&self) -> TokenStream {
+        let ident = &self.ident;
+        let ty = &self.ty;
+        match self.kind {
+            FieldKind::Fixed => quote! { ::core::mem::size_of::<#ty>() },
+            FieldKind::Bytes | FieldKind::Str => quote! {
+                ::pino_core::data::LENGTH_PREFIX_SIZE + self.#ident.len()
+            },
+        }
+    }
+
+    fn write(&self) -> TokenStream {
+        let ident = &self.ident;
+        match self.kind {
+            FieldKind::Fixed => quote! { writer.write(&self.#ident)?; },
+            FieldKind::Bytes => quote! { writer.write_bytes(self.#ident)?; },
+            FieldKind::Str => quote! { writer.write_str(self.#ident)?; },
+        }
+    }
+}
+
+/// Implementation of the PinoData derive macro
+pub fn derive_pino_data_impl(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "#[derive(PinoData)] requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "#[derive(PinoData)] can only be used on structs",
+            ))
+        }
+    };
+
+    if input.generics.lifetimes().count() > 1 {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(PinoData)] supports at most one lifetime",
+        ));
+    }
+
+    // Structs without borrowed fields may omit the lifetime; the impl still
+    // needs one to name the input.
+    let mut impl_generics_source = input.generics.clone();
+    let data_lifetime = match input.generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime: syn::Lifetime = syn::parse_quote!('a);
+            impl_generics_source
+                .params
+                .insert(0, syn::GenericParam::Lifetime(syn::LifetimeParam::new(lifetime.clone())));
+            lifetime
+        }
+    };
+
+    let data_fields = fields
+        .iter()
+        .map(|field| DataField {
+            ident: field.ident.clone().expect("named field"),
+            ty: field.ty.clone(),
+            kind: FieldKind::from_type(&field.ty),
+        })
+        .collect::<Vec<_>>();
+
+    let idents = data_fields.iter().map(|field| &field.ident);
+    let reads = data_fields.iter().map(DataField::read);
+    let lens = data_fields.iter().map(DataField::len);
+    let writes = data_fields.iter().map(DataField::write);
+
+    let type_name = name.to_string();
+    let idl_fields = crate::idl::fields(
+        data_fields
+            .iter()
+            .map(|field| (field.ident.to_string(), &field.ty)),
+    );
+    let idl = crate::idl::print_test(
+        &format!("type_{}", name),
+        quote! {
+            ::pino_core::idl::print(
+                "type",
+                &::pino_core::idl::IdlTypeDef::structure(#type_name, #idl_fields),
+            );
+        },
+    );
+
+    let (impl_generics, _, _) = impl_generics_source.split_for_impl();
+    let (plain_impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::pino_core::data::PinoData<#data_lifetime> for #name #ty_generics #where_clause {
+            #[allow(unused_mut)]
+            fn parse(data: &#data_lifetime [u8]) -> ::core::result::Result<Self, ::pino_core::error::PinoError> {
+                let mut __reader = ::pino_core::data::DataReader::new(data);
+                #(#reads)*
+                __reader.finish()?;
+                Ok(Self { #(#idents),* })
+            }
+
+            fn serialized_len(&self) -> usize {
+                0 #(+ #lens)*
+            }
+
+            #[allow(unused_mut)]
+            fn write_to(&self, buffer: &mut [u8]) -> ::core::result::Result<usize, ::pino_core::error::PinoError> {
+                let mut writer = ::pino_core::data::DataWriter::new(buffer);
+                #(#writes)*
+                Ok(writer.position())
+            }
+        }
+
+        impl #impl_generics ::pino_core::context::InstructionData<#data_lifetime> for #name #ty_generics #where_clause {
+            fn try_from_slice(data: &#data_lifetime [u8]) -> ::core::result::Result<Self, ::pino_core::error::PinoError> {
+                <Self as ::pino_core::data::PinoData<#data_lifetime>>::parse(data)
+            }
+        }
+
+        impl #plain_impl_generics ::pino_core::instruction::ToInstructionData for #name #ty_generics #where_clause {
+            fn to_instruction_data(&self) -> ::core::result::Result<::pino_core::data::Vec<u8>, ::pino_core::error::PinoError> {
+                ::pino_core::data::PinoData::to_vec(self)
+            }
+
+            fn write_instruction_data<const __N: usize>(
+                &self,
+                buffer: &mut ::pino_core::collections::StackVec<u8, __N>,
+            ) -> ::core::result::Result<(), ::pino_core::error::PinoError> {
+                ::pino_core::data::PinoData::write_to_stack_vec(self, buffer)
+            }
+        }
+
+        #idl
+    })
+}
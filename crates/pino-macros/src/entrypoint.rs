@@ -2,55 +2,411 @@
 
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{ItemMod, Result};
+use syn::{
+    visit_mut::VisitMut, Error, Expr, FnArg, GenericArgument, Ident, ItemMod, Lifetime, Meta,
+    PathArguments, Pat, Result, Signature, Type,
+};
+
+use crate::{
+    hooks::Hooks,
+    instruction::{check_unique, DiscriminatorAttr, DiscriminatorScheme},
+};
+
+/// An `#[instruction]` handler found in a program module or processor.
+pub struct Handler {
+    pub ident: Ident,
+    pub discriminator: Vec<u8>,
+    /// The `T` of the handler's `Context<T>` parameter.
+    accounts: Type,
+    /// The types of the arguments following the context.
+    args: Vec<Type>,
+    /// The names of those arguments, as exported to the IDL.
+    arg_names: Vec<String>,
+    /// The hooks run around the handler, program-wide ones included.
+    hooks: Hooks,
+}
+
+impl Handler {
+    pub fn from_sig(sig: &Signature, discriminator: Vec<u8>, hooks: Hooks) -> Result<Self> {
+        let mut inputs = sig.inputs.iter();
+
+        let accounts = match inputs.next() {
+            Some(FnArg::Typed(arg)) => context_accounts(&arg.ty)?,
+            _ => {
+                return Err(Error::new_spanned(
+                    sig,
+                    "instruction handlers must take `ctx: Context<T>` as their first argument",
+                ))
+            }
+        };
+
+        let (arg_names, args) = inputs
+            .enumerate()
+            .map(|(index, input)| match input {
+                FnArg::Typed(arg) => {
+                    let name = match &*arg.pat {
+                        Pat::Ident(pat) => pat.ident.to_string(),
+                        _ => format!("arg{}", index),
+                    };
+                    Ok((name, (*arg.ty).clone()))
+                }
+                FnArg::Receiver(receiver) => Err(Error::new_spanned(
+                    receiver,
+                    "instruction handlers cannot take `self`",
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+
+        Ok(Self {
+            ident: sig.ident.clone(),
+            discriminator,
+            accounts,
+            args,
+            arg_names,
+            hooks,
+        })
+    }
+
+    /// Generates the statement printing the handler's IDL fragment.
+    pub fn idl_instruction(&self) -> TokenStream {
+        let name = self.ident.to_string();
+        let discriminator = &self.discriminator;
+        let accounts = elide_lifetimes(&self.accounts);
+        let args = crate::idl::fields(self.arg_names.iter().cloned().zip(&self.args));
+
+        quote! {
+            ::pino_core::idl::print(
+                "instruction",
+                &::pino_core::idl::IdlInstruction::new(
+                    #name,
+                    &[#(#discriminator),*],
+                    <#accounts as ::pino_core::idl::IdlAccounts>::idl_accounts(),
+                    #args,
+                ),
+            );
+        }
+    }
+
+    /// Returns the generated names the decoded arguments are bound to, which
+    /// cannot shadow the dispatcher's own locals.
+    pub fn arg_idents(&self) -> Vec<Ident> {
+        (0..self.args.len())
+            .map(|index| quote::format_ident!("__arg_{}", index))
+            .collect()
+    }
+
+    /// Returns the argument types with their lifetimes replaced by
+    /// `lifetime`.
+    pub fn arg_types(&self, lifetime: &Lifetime) -> Vec<Type> {
+        self.args
+            .iter()
+            .map(|ty| replace_lifetimes(ty, lifetime))
+            .collect()
+    }
+
+    /// Generates the statement decoding the argument bytes `data` into the
+    /// names of `arg_idents`.
+    pub fn decode_args(&self, lifetime: &Lifetime) -> TokenStream {
+        let names = self.arg_idents();
+        let types = self.arg_types(lifetime);
+
+        // A single argument is decoded on its own so that `PinoData` views,
+        // which only implement `InstructionData` themselves, can be used.
+        match types.as_slice() {
+            [] => TokenStream::new(),
+            [ty] => quote! {
+                let __arg_0 = <#ty as ::pino_core::context::InstructionData>::try_from_slice(data)?;
+            },
+            _ => quote! {
+                let (#(#names,)*) =
+                    <(#(#types,)*) as ::pino_core::context::InstructionData>::try_from_slice(data)?;
+            },
+        }
+    }
+
+    /// Generates the match arm body that builds the context, runs the
+    /// handler through `call` between its hooks and finally runs
+    /// `Accounts::exit`.
+    ///
+    /// Expects `program_id`, the `RemainingAccounts` `accounts`, the argument
+    /// bytes `data` and the decoded arguments named by `arg_idents` in scope.
+    pub fn dispatch(&self, call: TokenStream) -> TokenStream {
+        let accounts = elide_lifetimes(&self.accounts);
+        let names = self.arg_idents();
+        let enter = self.hooks.enter();
+
+        // The handler consumes its context, so `after` hooks get a new one
+        // borrowing the same accounts; the remaining accounts were the
+        // handler's to take, and CPIs signed with its seeds are over.
+        let exit = if self.hooks.has_after() {
+            let after = self.hooks.exit();
+            quote! {
+                let ctx = __parsed.context(program_id, data);
+                #after
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        quote! {
+            let mut __slots = ::core::default::Default::default();
+            let mut __parsed = ::pino_core::entrypoint::parse_accounts::<#accounts>(
+                program_id,
+                accounts,
+                &mut __slots,
+                data,
+            )?;
+            let ctx = __parsed.context(program_id, data);
+            #enter
+            #call(ctx, #(#names),*)?;
+            #exit
+            ::pino_core::context::Accounts::exit(&__parsed.accounts, program_id)
+        }
+    }
+}
+
+/// Replaces the named lifetimes of an argument type with `'_`, since the
+/// handler's lifetime parameters are not in scope in the dispatcher.
+pub fn elide_lifetimes(ty: &Type) -> Type {
+    replace_lifetimes(ty, &Lifetime::new("'_", proc_macro2::Span::call_site()))
+}
+
+/// Replaces the non-`'static` lifetimes of a type with `lifetime`.
+fn replace_lifetimes(ty: &Type, lifetime: &Lifetime) -> Type {
+    struct Replace<'a>(&'a Lifetime);
+
+    impl VisitMut for Replace<'_> {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            if lifetime.ident != "static" {
+                *lifetime = Lifetime::new(&self.0.to_string(), lifetime.span());
+            }
+        }
+    }
+
+    let mut ty = ty.clone();
+    Replace(lifetime).visit_type_mut(&mut ty);
+    ty
+}
+
+/// Extracts `T` from a `Context<T>` parameter type.
+fn context_accounts(ty: &Type) -> Result<Type> {
+    let error = || Error::new_spanned(ty, "expected `Context<T>`");
+
+    let Type::Path(type_path) = ty else {
+        return Err(error());
+    };
+    let segment = type_path.path.segments.last().ok_or_else(error)?;
+    if segment.ident != "Context" {
+        return Err(error());
+    }
+    let PathArguments::AngleBracketed(generics) = &segment.arguments else {
+        return Err(error());
+    };
+
+    generics
+        .args
+        .iter()
+        .find_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        })
+        .ok_or_else(error)
+}
+
+/// The entrypoint options of `#[pino_program(...)]`.
+#[derive(Default)]
+struct EntrypointOptions {
+    /// The `#[error_code]` enum whose codes are logged.
+    errors: Option<Expr>,
+    /// The most accounts an instruction can pass, 64 by default.
+    max_accounts: Option<Expr>,
+    /// One of the allocators of `pino_entrypoint!`.
+    allocator: Option<Expr>,
+    /// The heap size given to the allocator.
+    heap_size: Option<Expr>,
+    /// One of the panic handlers of `pino_entrypoint!`.
+    panic_handler: Option<Expr>,
+}
+
+impl EntrypointOptions {
+    /// Reads the options from the attribute arguments, leaving
+    /// `discriminator` to `DiscriminatorScheme`.
+    fn from_args(args: &[Meta]) -> Result<Self> {
+        let mut options = Self::default();
+
+        for arg in args {
+            let Meta::NameValue(name_value) = arg else {
+                return Err(Error::new_spanned(arg, "expected `<option> = <value>`"));
+            };
+            let option = if name_value.path.is_ident("errors") {
+                &mut options.errors
+            } else if name_value.path.is_ident("max_accounts") {
+                &mut options.max_accounts
+            } else if name_value.path.is_ident("allocator") {
+                &mut options.allocator
+            } else if name_value.path.is_ident("heap_size") {
+                &mut options.heap_size
+            } else if name_value.path.is_ident("panic_handler") {
+                &mut options.panic_handler
+            } else if name_value.path.is_ident("discriminator") {
+                continue;
+            } else {
+                return Err(Error::new_spanned(
+                    &name_value.path,
+                    "unknown option; expected `discriminator`, `errors`, `max_accounts`, \
+                     `allocator`, `heap_size` or `panic_handler`",
+                ));
+            };
+            *option = Some(name_value.value.clone());
+        }
+
+        Ok(options)
+    }
+}
 
 /// Implementation of the pino_program macro
-pub fn pino_program_impl(_args: Vec<syn::Meta>, input: ItemMod) -> Result<TokenStream> {
-    let mod_name = &input.ident;
-    let mod_content = input.content.as_ref().map(|(_, items)| items);
+pub fn pino_program_impl(args: Vec<Meta>, mut input: ItemMod) -> Result<TokenStream> {
+    let scheme = DiscriminatorScheme::from_args(&args)?;
+    let options = EntrypointOptions::from_args(&args)?;
+
+    let hooks = Hooks::take(&mut input.attrs)?;
+    let Some((_, items)) = input.content.as_mut() else {
+        return Err(Error::new_spanned(
+            &input,
+            "#[pino_program] requires an inline module",
+        ));
+    };
 
     // Extract function handlers
     let mut handlers = Vec::new();
-    if let Some(items) = mod_content {
-        for item in items {
-            if let syn::Item::Fn(func) = item {
-                // Check if function has #[instruction] attribute
-                for attr in &func.attrs {
-                    if attr.path().is_ident("instruction") {
-                        handlers.push(&func.sig.ident);
-                        break;
-                    }
+    let mut fallback = None;
+    for item in items.iter_mut() {
+        if let syn::Item::Fn(func) = item {
+            if let Some(position) = func
+                .attrs
+                .iter()
+                .position(|attr| attr.path().is_ident("fallback"))
+            {
+                let attr = func.attrs.remove(position);
+                attr.meta.require_path_only()?;
+                if fallback.is_some() {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "a program can only have one #[fallback] function",
+                    ));
                 }
+                fallback = Some(func.sig.ident.clone());
+                continue;
             }
+
+            let Some(position) = func
+                .attrs
+                .iter()
+                .position(|attr| attr.path().is_ident("instruction"))
+            else {
+                continue;
+            };
+            let attr = func.attrs.remove(position);
+            let discriminator = DiscriminatorAttr::from_attr(&attr)?.resolve(
+                scheme,
+                handlers.len(),
+                &func.sig.ident,
+            )?;
+            let handler_hooks = hooks.around(Hooks::take(&mut func.attrs)?);
+            handlers.push(Handler::from_sig(&func.sig, discriminator, handler_hooks)?);
         }
     }
 
-    // Generate entrypoint
-    let entrypoint_name = quote::format_ident!("{}_entrypoint", mod_name);
-    
-    Ok(quote! {
-        #input
+    check_unique(
+        &handlers
+            .iter()
+            .map(|handler| (&handler.ident, handler.discriminator.as_slice()))
+            .collect::<Vec<_>>(),
+    )?;
 
-        /// Program entrypoint
-        #[no_mangle]
-        pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
-            let (program_id, accounts, instruction_data) = 
-                unsafe { ::pino_core::pinocchio::entrypoint::parse_input(input) };
-            
-            match process_instruction(&program_id, accounts, instruction_data) {
-                Ok(()) => ::pino_core::SUCCESS,
-                Err(_) => 1, // Error code
+    // Slice patterns let discriminators of different lengths share one match.
+    let elided = Lifetime::new("'_", proc_macro2::Span::call_site());
+    let arms = handlers.iter().map(|handler| {
+        let bytes = &handler.discriminator;
+        let ident = &handler.ident;
+        let decode_args = handler.decode_args(&elided);
+        let dispatch = handler.dispatch(quote! { #ident });
+        quote! {
+            [#(#bytes,)* data @ ..] => {
+                let accounts = ::pino_core::context::RemainingAccounts::Parsed(accounts);
+                #decode_args
+                #dispatch
             }
         }
+    });
+    let fallback = match fallback {
+        Some(fallback) => quote! { #fallback(program_id, accounts, instruction_data) },
+        None => quote! {
+            Err(::pino_core::error::PinoError::InstructionFallbackNotFound.into())
+        },
+    };
 
+    let dispatcher: syn::Item = syn::parse_quote! {
         /// Process instruction dispatcher
+        #[allow(unused_variables)]
         pub fn process_instruction(
             program_id: &::pino_core::Pubkey,
             accounts: &[::pino_core::AccountInfo],
             instruction_data: &[u8],
         ) -> ::pino_core::ProgramResult {
-            // For now, return success - proper routing would be implemented here
-            Ok(())
+            match instruction_data {
+                #(#arms)*
+                _ => #fallback,
+            }
+        }
+    };
+
+    let errors = options.errors.map_or_else(|| quote! { () }, |errors| quote! { #errors });
+    let max_accounts = options.max_accounts.map_or_else(|| quote! { 64 }, |max| quote! { #max });
+    let entrypoint: syn::Item = syn::parse_quote! {
+        /// Program entrypoint
+        #[no_mangle]
+        pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
+            const MAX_ACCOUNTS: usize = #max_accounts;
+
+            let (program_id, accounts, instruction_data) =
+                ::pino_core::pinocchio::entrypoint::deserialize::<MAX_ACCOUNTS>(input, &mut [
+                    core::mem::MaybeUninit::uninit(); MAX_ACCOUNTS
+                ]);
+
+            match process_instruction(
+                &program_id,
+                core::slice::from_raw_parts(accounts.as_ptr() as _, accounts.len()),
+                &instruction_data,
+            ) {
+                Ok(()) => ::pino_core::SUCCESS,
+                Err(error) => {
+                    ::pino_core::error::log_program_error::<#errors>(&error);
+                    error.into()
+                }
+            }
         }
-    })
-} 
\ No newline at end of file
+    };
+
+    let idl_instructions = handlers.iter().map(Handler::idl_instruction);
+
+    items.push(dispatcher);
+    items.push(entrypoint);
+    items.push(syn::Item::Verbatim(crate::idl::print_test(
+        "instructions",
+        quote! { #(#idl_instructions)* },
+    )));
+    let allocator = options.allocator;
+    let heap_size = options.heap_size;
+    items.push(syn::parse_quote! {
+        ::pino_core::__pino_allocator!(#allocator; #heap_size);
+    });
+    let panic_handler = options.panic_handler;
+    items.push(syn::parse_quote! {
+        ::pino_core::__pino_panic_handler!(#panic_handler);
+    });
+
+    Ok(quote! { #input })
+}
@@ -0,0 +1,185 @@
+//! Error code macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Expr, Fields, ItemEnum, Lit, LitStr, Meta, Result};
+
+/// Codes start here unless `#[error_code(offset = ...)]` says otherwise,
+/// matching Anchor's custom error range.
+const DEFAULT_OFFSET: u32 = 6000;
+
+/// Codes used by `PinoError`, from `ERROR_CODE_MIN` to `ERROR_CODE_MAX`.
+const FRAMEWORK_CODES: (u32, u32) = (1000, 5999);
+
+/// Reads `offset = <u32>` from the attribute arguments.
+fn offset(args: &[Meta]) -> Result<u32> {
+    let mut offset = DEFAULT_OFFSET;
+
+    for arg in args {
+        match arg {
+            Meta::NameValue(name_value) if name_value.path.is_ident("offset") => {
+                let Expr::Lit(syn::ExprLit { lit: Lit::Int(lit), .. }) = &name_value.value else {
+                    return Err(Error::new_spanned(
+                        &name_value.value,
+                        "expected an integer offset",
+                    ));
+                };
+                offset = lit.base10_parse()?;
+            }
+            _ => return Err(Error::new_spanned(arg, "expected `offset = <u32>`")),
+        }
+    }
+
+    Ok(offset)
+}
+
+/// Implementation of the error_code macro
+pub fn error_code_impl(args: Vec<Meta>, mut input: ItemEnum) -> Result<TokenStream> {
+    let offset = offset(&args)?;
+    let name = &input.ident;
+
+    if input.variants.is_empty() {
+        return Err(Error::new_spanned(name, "#[error_code] requires at least one variant"));
+    }
+
+    let mut variants = Vec::new();
+    let mut next = 0u32;
+    for variant in input.variants.iter_mut() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "#[error_code] variants cannot carry data",
+            ));
+        }
+
+        // An explicit discriminant restarts the numbering, as in Rust.
+        if let Some((_, discriminant)) = &variant.discriminant {
+            let Expr::Lit(syn::ExprLit { lit: Lit::Int(lit), .. }) = discriminant else {
+                return Err(Error::new_spanned(
+                    discriminant,
+                    "expected an integer discriminant",
+                ));
+            };
+            next = lit.base10_parse()?;
+        }
+        let code = offset.checked_add(next).ok_or_else(|| {
+            Error::new_spanned(&variant.ident, "error code does not fit in a `u32`")
+        })?;
+        if (FRAMEWORK_CODES.0..=FRAMEWORK_CODES.1).contains(&code) {
+            return Err(Error::new_spanned(
+                &variant.ident,
+                format!(
+                    "error code {} is reserved for framework errors ({}..={})",
+                    code, FRAMEWORK_CODES.0, FRAMEWORK_CODES.1
+                ),
+            ));
+        }
+        next += 1;
+
+        let message = match variant.attrs.iter().position(|attr| attr.path().is_ident("msg")) {
+            Some(position) => variant.attrs.remove(position).parse_args::<LitStr>()?.value(),
+            None => variant.ident.to_string(),
+        };
+
+        variants.push((variant.ident.clone(), code, message));
+    }
+
+    for (index, (ident, code, _)) in variants.iter().enumerate() {
+        if let Some((other, _, _)) = variants[..index].iter().find(|(_, other, _)| other == code) {
+            return Err(Error::new_spanned(
+                ident,
+                format!("error code {} is already used by `{}`", code, other),
+            ));
+        }
+    }
+
+    let code_arms = variants.iter().map(|(ident, code, _)| quote! { Self::#ident => #code, });
+    let name_arms = variants.iter().map(|(ident, _, _)| {
+        let name = ident.to_string();
+        quote! { Self::#ident => #name, }
+    });
+    let message_arms = variants
+        .iter()
+        .map(|(ident, _, message)| quote! { Self::#ident => #message, });
+    // The log line is assembled here so that no formatting happens on-chain.
+    let log_arms = variants.iter().map(|(ident, code, message)| {
+        let line = format!(
+            "Error Code: {}. Error Number: {}. Error Message: {}.",
+            ident, code, message
+        );
+        quote! { #code => ::core::option::Option::Some(#line), }
+    });
+
+    let idl_errors = variants.iter().map(|(ident, code, message)| {
+        let name = ident.to_string();
+        quote! {
+            ::pino_core::idl::print(
+                "error",
+                &::pino_core::idl::IdlErrorCode::new(#code, #name, Some(#message)),
+            );
+        }
+    });
+    let idl = crate::idl::print_test(
+        &format!("errors_{}", name),
+        quote! { #(#idl_errors)* },
+    );
+
+    Ok(quote! {
+        #input
+
+        impl #name {
+            /// Code of the first variant.
+            pub const OFFSET: u32 = #offset;
+
+            /// Returns the code carried by `ProgramError::Custom`.
+            pub const fn code(&self) -> u32 {
+                match self {
+                    #(#code_arms)*
+                }
+            }
+
+            /// Returns the variant name.
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    #(#name_arms)*
+                }
+            }
+
+            /// Returns the `#[msg]` of the variant, or its name.
+            pub const fn message(&self) -> &'static str {
+                match self {
+                    #(#message_arms)*
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(self.message())
+            }
+        }
+
+        impl ::core::convert::From<#name> for ::pino_core::error::PinoError {
+            fn from(error: #name) -> Self {
+                ::pino_core::error::PinoError::Custom(error.code())
+            }
+        }
+
+        impl ::core::convert::From<#name> for ::pino_core::pinocchio::program_error::ProgramError {
+            fn from(error: #name) -> Self {
+                ::pino_core::error::PinoError::from(error).into()
+            }
+        }
+
+        impl ::pino_core::error::ProgramErrors for #name {
+            fn log_line(code: u32) -> ::core::option::Option<&'static str> {
+                match code {
+                    #(#log_arms)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+
+        #idl
+    })
+}
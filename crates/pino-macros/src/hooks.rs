@@ -0,0 +1,80 @@
+//! Instruction hook parsing.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Path, Result};
+
+/// The `#[access_control(f)]`, `#[before(f)]` and `#[after(f)]` hooks of a
+/// handler, or of every handler of a program.
+///
+/// Each hook is called with `&Context<T>` and returns a `Result`, whose
+/// error short-circuits the instruction.
+#[derive(Default)]
+pub struct Hooks {
+    access_control: Vec<Path>,
+    before: Vec<Path>,
+    after: Vec<Path>,
+}
+
+impl Hooks {
+    /// Removes the hook attributes from `attrs`, keeping their order.
+    pub fn take(attrs: &mut Vec<Attribute>) -> Result<Self> {
+        let mut hooks = Self::default();
+        let mut kept = Vec::with_capacity(attrs.len());
+
+        for attr in attrs.drain(..) {
+            let list = if attr.path().is_ident("access_control") {
+                &mut hooks.access_control
+            } else if attr.path().is_ident("before") {
+                &mut hooks.before
+            } else if attr.path().is_ident("after") {
+                &mut hooks.after
+            } else {
+                kept.push(attr);
+                continue;
+            };
+            list.push(attr.parse_args()?);
+        }
+
+        *attrs = kept;
+        Ok(hooks)
+    }
+
+    /// Combines program-wide hooks with the hooks of a handler: the
+    /// program's run first on the way in and last on the way out.
+    pub fn around(&self, handler: Self) -> Self {
+        Self {
+            access_control: self
+                .access_control
+                .iter()
+                .cloned()
+                .chain(handler.access_control)
+                .collect(),
+            before: self.before.iter().cloned().chain(handler.before).collect(),
+            after: handler.after.into_iter().chain(self.after.iter().cloned()).collect(),
+        }
+    }
+
+    /// Generates the calls made before the handler, with the context `ctx`
+    /// in scope: access control first, then the `before` hooks.
+    pub fn enter(&self) -> TokenStream {
+        let hooks = self.access_control.iter().chain(&self.before);
+        quote! {
+            #(#hooks(&ctx)?;)*
+        }
+    }
+
+    /// Generates the calls made after the handler, with the context `ctx`
+    /// in scope.
+    pub fn exit(&self) -> TokenStream {
+        let hooks = &self.after;
+        quote! {
+            #(#hooks(&ctx)?;)*
+        }
+    }
+
+    /// Returns whether there are `after` hooks.
+    pub fn has_after(&self) -> bool {
+        !self.after.is_empty()
+    }
+}
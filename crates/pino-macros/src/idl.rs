@@ -0,0 +1,154 @@
+//! IDL metadata generation.
+//!
+//! Everything emitted here is gated on the program's `idl-build` feature and
+//! only runs as `#[test]` functions on the host.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Expr, GenericArgument, Ident, Lit, PathArguments, Type};
+
+/// Wraps `body` in a test function that `pino idl build` runs to collect the
+/// IDL fragments printed by `body`.
+pub fn print_test(name: &str, body: TokenStream) -> TokenStream {
+    let ident = format_ident!("__pino_idl_print_{}", name);
+
+    quote! {
+        #[cfg(all(test, feature = "idl-build"))]
+        #[test]
+        #[allow(non_snake_case)]
+        fn #ident() {
+            #body
+        }
+    }
+}
+
+/// Maps a Rust type to the expression building its `IdlType`.
+pub fn idl_type(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Slice(slice) if is_ident(&slice.elem, "u8") => {
+                quote! { ::pino_core::idl::IdlType::Bytes }
+            }
+            elem if is_ident(elem, "str") => quote! { ::pino_core::idl::IdlType::String },
+            elem => idl_type(elem),
+        },
+        Type::Array(array) => {
+            let elem = idl_type(&array.elem);
+            let len = &array.len;
+            quote! { ::pino_core::idl::IdlType::array(#elem, (#len) as usize) }
+        }
+        Type::Paren(paren) => idl_type(&paren.elem),
+        Type::Group(group) => idl_type(&group.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let Some(segment) = path.path.segments.last() else {
+                return defined(ty);
+            };
+
+            let primitive = match segment.ident.to_string().as_str() {
+                "bool" => Some(quote! { Bool }),
+                "u8" => Some(quote! { U8 }),
+                "i8" => Some(quote! { I8 }),
+                "u16" => Some(quote! { U16 }),
+                "i16" => Some(quote! { I16 }),
+                "u32" => Some(quote! { U32 }),
+                "i32" => Some(quote! { I32 }),
+                "u64" => Some(quote! { U64 }),
+                "i64" => Some(quote! { I64 }),
+                "u128" => Some(quote! { U128 }),
+                "i128" => Some(quote! { I128 }),
+                "Pubkey" => Some(quote! { Pubkey }),
+                "String" => Some(quote! { String }),
+                _ => None,
+            };
+            if let Some(primitive) = primitive {
+                return quote! { ::pino_core::idl::IdlType::#primitive };
+            }
+
+            match (segment.ident.to_string().as_str(), generic_type(&segment.arguments)) {
+                ("Vec", Some(elem)) if is_ident(elem, "u8") => {
+                    quote! { ::pino_core::idl::IdlType::Bytes }
+                }
+                ("Vec", Some(elem)) => {
+                    let elem = idl_type(elem);
+                    quote! { ::pino_core::idl::IdlType::vec(#elem) }
+                }
+                ("Option", Some(elem)) => {
+                    let elem = idl_type(elem);
+                    quote! { ::pino_core::idl::IdlType::option(#elem) }
+                }
+                _ => {
+                    let name = segment.ident.to_string();
+                    quote! { ::pino_core::idl::IdlType::defined(#name) }
+                }
+            }
+        }
+        _ => defined(ty),
+    }
+}
+
+fn defined(ty: &Type) -> TokenStream {
+    let name = quote!(#ty).to_string();
+    quote! { ::pino_core::idl::IdlType::defined(#name) }
+}
+
+fn is_ident(ty: &Type, name: &str) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident(name))
+}
+
+/// Returns the first type argument of `Vec<T>`-like path arguments.
+fn generic_type(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(arguments) = arguments else {
+        return None;
+    };
+    arguments.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+/// Builds the `Vec<IdlField>` expression for named, typed fields.
+pub fn fields<'a>(fields: impl IntoIterator<Item = (String, &'a Type)>) -> TokenStream {
+    let fields = fields.into_iter().map(|(name, ty)| {
+        let ty = idl_type(ty);
+        quote! { ::pino_core::idl::IdlField::new(#name, #ty) }
+    });
+
+    quote! { ::pino_core::idl::Vec::from([#(#fields),*]) }
+}
+
+/// Describes a PDA seed expression, if it is a constant byte string or the
+/// key of one of `accounts`.
+///
+/// Seeds computed from anything else cannot be described, in which case the
+/// whole PDA is left out of the IDL.
+pub fn seed(expr: &Expr, accounts: &[&Ident]) -> Option<TokenStream> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::ByteStr(bytes) => {
+                let value = bytes.value();
+                Some(quote! { ::pino_core::idl::IdlSeed::constant(&[#(#value),*]) })
+            }
+            _ => None,
+        },
+        Expr::Reference(reference) => seed(&reference.expr, accounts),
+        Expr::Paren(paren) => seed(&paren.expr, accounts),
+        // `authority.key()`
+        Expr::MethodCall(call) if call.method == "key" => {
+            let Expr::Path(path) = &*call.receiver else {
+                return None;
+            };
+            let ident = path.path.get_ident()?;
+            accounts.contains(&ident).then(|| {
+                let name = ident.to_string();
+                quote! { ::pino_core::idl::IdlSeed::account(#name) }
+            })
+        }
+        // `b"vault".as_ref()`, `authority.key().as_ref()`
+        Expr::MethodCall(call)
+            if call.method == "as_ref" || call.method == "as_slice" || call.method == "as_bytes" =>
+        {
+            seed(&call.receiver, accounts)
+        }
+        _ => None,
+    }
+}
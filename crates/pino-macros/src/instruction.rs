@@ -0,0 +1,181 @@
+//! Instruction discriminator parsing.
+
+use sha2::{Digest, Sha256};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Error, Ident, LitInt, Meta, Result, Token,
+};
+
+/// How discriminators are derived for handlers that do not declare one.
+#[derive(Clone, Copy)]
+pub enum DiscriminatorScheme {
+    /// A single byte holding the handler's position in the program.
+    Index,
+    /// The first 8 bytes of `sha256("global:<name>")`, as used by Anchor.
+    Hashed,
+}
+
+impl DiscriminatorScheme {
+    /// Reads `discriminator = index | hashed` from the program attribute.
+    pub fn from_args(args: &[Meta]) -> Result<Self> {
+        let mut scheme = DiscriminatorScheme::Index;
+
+        for arg in args {
+            let Meta::NameValue(name_value) = arg else {
+                continue;
+            };
+            if !name_value.path.is_ident("discriminator") {
+                continue;
+            }
+
+            let syn::Expr::Path(value) = &name_value.value else {
+                return Err(Error::new_spanned(
+                    &name_value.value,
+                    "expected `discriminator = index` or `discriminator = hashed`",
+                ));
+            };
+            scheme = if value.path.is_ident("index") {
+                DiscriminatorScheme::Index
+            } else if value.path.is_ident("hashed") {
+                DiscriminatorScheme::Hashed
+            } else {
+                return Err(Error::new_spanned(
+                    value,
+                    "expected `discriminator = index` or `discriminator = hashed`",
+                ));
+            };
+        }
+
+        Ok(scheme)
+    }
+}
+
+/// The arguments of an `#[instruction(...)]` attribute.
+pub enum DiscriminatorAttr {
+    /// `#[instruction]`: derived from the program's scheme.
+    Auto,
+    /// `#[instruction(hashed)]`
+    Hashed,
+    /// `#[instruction(0x01)]`, `#[instruction(7u32)]` or `#[instruction([1, 2, 3])]`
+    Explicit(Vec<u8>),
+}
+
+impl DiscriminatorAttr {
+    /// Parses the discriminator of an `#[instruction]` attribute.
+    pub fn from_attr(attr: &Attribute) -> Result<Self> {
+        match &attr.meta {
+            Meta::Path(_) => Ok(DiscriminatorAttr::Auto),
+            Meta::List(_) => attr.parse_args(),
+            Meta::NameValue(_) => Err(Error::new_spanned(
+                attr,
+                "expected `#[instruction]` or `#[instruction(<discriminator>)]`",
+            )),
+        }
+    }
+
+    /// Returns the discriminator bytes of the handler at `index` named `name`.
+    pub fn resolve(&self, scheme: DiscriminatorScheme, index: usize, name: &Ident) -> Result<Vec<u8>> {
+        match (self, scheme) {
+            (DiscriminatorAttr::Explicit(bytes), _) => Ok(bytes.clone()),
+            (DiscriminatorAttr::Hashed, _) | (DiscriminatorAttr::Auto, DiscriminatorScheme::Hashed) => {
+                Ok(hashed(&name.to_string()).to_vec())
+            }
+            (DiscriminatorAttr::Auto, DiscriminatorScheme::Index) => u8::try_from(index)
+                .map(|index| vec![index])
+                .map_err(|_| {
+                    Error::new(
+                        name.span(),
+                        "index discriminators support at most 256 instructions",
+                    )
+                }),
+        }
+    }
+}
+
+impl Parse for DiscriminatorAttr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(DiscriminatorAttr::Auto);
+        }
+
+        if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            let bytes = Punctuated::<LitInt, Token![,]>::parse_terminated(&content)?
+                .iter()
+                .map(|lit| lit.base10_parse::<u8>())
+                .collect::<Result<Vec<_>>>()?;
+            if bytes.is_empty() {
+                return Err(Error::new(input.span(), "discriminators cannot be empty"));
+            }
+            return Ok(DiscriminatorAttr::Explicit(bytes));
+        }
+
+        if input.peek(LitInt) {
+            let lit: LitInt = input.parse()?;
+            let bytes = match lit.suffix() {
+                "" | "u8" => lit.base10_parse::<u8>().map(|value| vec![value]).map_err(|_| {
+                    Error::new(
+                        lit.span(),
+                        "unsuffixed discriminators must fit in a `u8`; add a `u16`, `u32` or `u64` suffix",
+                    )
+                })?,
+                "u16" => lit.base10_parse::<u16>()?.to_le_bytes().to_vec(),
+                "u32" => lit.base10_parse::<u32>()?.to_le_bytes().to_vec(),
+                "u64" => lit.base10_parse::<u64>()?.to_le_bytes().to_vec(),
+                suffix => {
+                    return Err(Error::new(
+                        lit.span(),
+                        format!("unsupported discriminator type `{}`", suffix),
+                    ))
+                }
+            };
+            return Ok(DiscriminatorAttr::Explicit(bytes));
+        }
+
+        let ident: Ident = input.parse()?;
+        if ident == "hashed" {
+            Ok(DiscriminatorAttr::Hashed)
+        } else {
+            Err(Error::new(
+                ident.span(),
+                "expected an integer, a byte array or `hashed`",
+            ))
+        }
+    }
+}
+
+/// Computes the Anchor-compatible discriminator of an instruction.
+pub fn hashed(name: &str) -> [u8; 8] {
+    sighash("global", name)
+}
+
+/// Returns the first 8 bytes of `sha256("<namespace>:<name>")`.
+pub fn sighash(namespace: &str, name: &str) -> [u8; 8] {
+    let digest = Sha256::digest(format!("{}:{}", namespace, name).as_bytes());
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&digest[..8]);
+    discriminator
+}
+
+/// Rejects discriminators that are equal to, or a prefix of, another one.
+///
+/// Either case would make dispatch ambiguous.
+pub fn check_unique(discriminators: &[(&Ident, &[u8])]) -> Result<()> {
+    for (index, (name, bytes)) in discriminators.iter().enumerate() {
+        for (other_name, other_bytes) in &discriminators[..index] {
+            if bytes.starts_with(other_bytes) || other_bytes.starts_with(bytes) {
+                return Err(Error::new(
+                    name.span(),
+                    format!(
+                        "discriminator of `{}` collides with the discriminator of `{}`",
+                        name, other_name
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,65 +1,163 @@
 //! Procedural macros for the Pino Solana framework.
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput, ItemFn, ItemMod};
+use syn::{
+    parse_macro_input, punctuated::Punctuated, DeriveInput, ItemEnum, ItemImpl, ItemMod, Meta, Token,
+};
 
+mod account;
+mod accounts;
+mod cpi;
+mod data;
 mod entrypoint;
+mod error;
+mod hooks;
+mod idl;
+mod instruction;
+mod processor;
 
 /// Derive macro for Accounts - generates account context validation
 #[proc_macro_derive(Accounts, attributes(account))]
 pub fn derive_accounts(input: TokenStream) -> TokenStream {
-    let _input = parse_macro_input!(input as DeriveInput);
-    // Phase 1: Return empty implementation
-    TokenStream::new()
+    let input = parse_macro_input!(input as DeriveInput);
+
+    accounts::derive_accounts_impl(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derive macro for CpiAccounts - lists the accounts of a CPI in field order
+///
+/// Fields are `&AccountInfo` or account wrappers such as `Signer` and
+/// `Account<T>`. They are passed read-only and not signing unless marked
+/// `#[cpi(mut)]` or `#[cpi(signer)]`.
+#[proc_macro_derive(CpiAccounts, attributes(cpi))]
+pub fn derive_cpi_accounts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    cpi::derive_cpi_accounts_impl(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 /// Attribute macro for instruction handlers
 #[proc_macro_attribute]
-pub fn instruction(_args: TokenStream, input: TokenStream) -> TokenStream {
-    // Phase 1: Return input unchanged
+pub fn instruction(args: TokenStream, input: TokenStream) -> TokenStream {
+    // Handlers are routed by #[pino_program]; here the discriminator is only
+    // validated so that mistakes surface on the handler itself.
+    if let Err(err) = syn::parse::<instruction::DiscriminatorAttr>(args) {
+        return err.to_compile_error().into();
+    }
     input
 }
 
-/// Attribute macro for account structs
+/// Attribute macro for account structs - alias of `#[pino_account]`
 #[proc_macro_attribute]
-pub fn account(_args: TokenStream, input: TokenStream) -> TokenStream {
-    // Phase 1: Return input unchanged
-    input
+pub fn account(args: TokenStream, input: TokenStream) -> TokenStream {
+    pino_account(args, input)
+}
+
+/// Attribute macro for account structs - checks the layout and assigns a
+/// discriminator so that `Account<T>` can verify what it loads
+///
+/// Accounts must be owned by the `crate::ID` of the program declaring the
+/// type, or by the program given as `#[pino_account(owner = OTHER_ID)]`.
+#[proc_macro_attribute]
+pub fn pino_account(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Meta, Token![,]>::parse_terminated);
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let account_data = account::owner(&args.into_iter().collect::<Vec<_>>())
+        .and_then(|owner| account::account_data_impl(&input, owner))
+        .unwrap_or_else(|err| err.to_compile_error());
+
+    quote::quote! {
+        #input
+        #account_data
+    }
+    .into()
 }
 
-/// Derive macro for PinoAccount - generates zero-copy account wrapper
+/// Derive macro for PinoAccount - same as `#[pino_account]`, for types that
+/// prefer a derive and are owned by `crate::ID`
 #[proc_macro_derive(PinoAccount)]
 pub fn derive_pino_account(input: TokenStream) -> TokenStream {
-    let _input = parse_macro_input!(input as DeriveInput);
-    // Phase 1: Return empty implementation
-    TokenStream::new()
+    let input = parse_macro_input!(input as DeriveInput);
+
+    account::account_data_impl(&input, None)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 /// Attribute macro for pino_program - generates program entrypoint and routing
+///
+/// Handlers may be wrapped in hooks taking `&Context<T>` and returning a
+/// `Result`, whose error skips the rest of the instruction:
+/// `#[access_control(f)]` and `#[before(f)]` run before the handler, in that
+/// order, and `#[after(f)]` after it, before `Accounts::exit`. Hooks placed
+/// on the module apply to every handler, outside the handler's own.
+///
+/// Instruction data matching no discriminator goes to the `#[fallback]`
+/// function, called with the program ID, the raw accounts and the full
+/// instruction data and run without hooks. Without one it fails with
+/// `PinoError::InstructionFallbackNotFound`.
+///
+/// `#[pino_program(errors = MyError)]` has the entrypoint log the codes of an
+/// `#[error_code]` enum by name and message. The `max_accounts`, `allocator`,
+/// `heap_size` and `panic_handler` options configure the entrypoint as in
+/// `pino_entrypoint!`, e.g.
+/// `#[pino_program(max_accounts = 16, allocator = region(4096), panic_handler = pino)]`.
 #[proc_macro_attribute]
-pub fn pino_program(_args: TokenStream, input: TokenStream) -> TokenStream {
-    let input_clone = input.clone();
-    let input_parsed = parse_macro_input!(input as ItemMod);
-    
-    // Use the entrypoint implementation
-    match entrypoint::pino_program_impl(Vec::new(), input_parsed) {
-        Ok(tokens) => tokens.into(),
-        Err(_) => input_clone, // Fallback to original input on error
-    }
+pub fn pino_program(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Meta, Token![,]>::parse_terminated);
+    let input = parse_macro_input!(input as ItemMod);
+
+    entrypoint::pino_program_impl(args.into_iter().collect(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
-/// Derive macro for instruction data - generates borsh serialization
+/// Derive macro for instruction data - generates a zero-copy parser and a matching serializer
 #[proc_macro_derive(PinoData)]
 pub fn derive_pino_data(input: TokenStream) -> TokenStream {
-    let _input = parse_macro_input!(input as DeriveInput);
-    // Phase 1: Return empty implementation
-    TokenStream::new()
+    let input = parse_macro_input!(input as DeriveInput);
+
+    data::derive_pino_data_impl(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 /// Attribute macro for processors - generates instruction processor
+///
+/// Routes the `#[instruction]` functions of an impl block on a unit struct,
+/// generating a `<Name>Instruction` enum of the decoded instructions and the
+/// `ProgramInstruction` implementation used by `pino_entrypoint!`. Hooks
+/// and the `#[fallback]` function work as in `#[pino_program]`, with
+/// impl-wide hooks placed on the impl and the fallback taking the accounts
+/// as `RemainingAccounts`.
 #[proc_macro_attribute]
-pub fn pino_processor(_args: TokenStream, input: TokenStream) -> TokenStream {
-    // Phase 1: Return input unchanged
-    input
-} 
\ No newline at end of file
+pub fn pino_processor(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Meta, Token![,]>::parse_terminated);
+    let input = parse_macro_input!(input as ItemImpl);
+
+    processor::pino_processor_impl(args.into_iter().collect(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+} 
+
+/// Attribute macro for program error enums - assigns codes from an offset
+///
+/// `#[error_code(offset = 6000)]` numbers the variants from the offset (6000
+/// by default) and generates `From` conversions into `PinoError` and
+/// `ProgramError`. A `#[msg("...")]` on a variant is exported to the IDL and
+/// logged when the error fails an instruction, by entrypoints given the enum
+/// as their `errors` option.
+#[proc_macro_attribute]
+pub fn error_code(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Meta, Token![,]>::parse_terminated);
+    let input = parse_macro_input!(input as ItemEnum);
+
+    error::error_code_impl(args.into_iter().collect(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
@@ -0,0 +1,277 @@
+//! Processor macro implementation.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{visit_mut::VisitMut, Error, Ident, ImplItem, ItemImpl, Lifetime, Meta, Result, Type};
+
+use crate::{
+    entrypoint::Handler,
+    hooks::Hooks,
+    instruction::{check_unique, DiscriminatorAttr, DiscriminatorScheme},
+};
+
+/// Converts a `snake_case` handler name to an `UpperCamelCase` variant name.
+fn variant_ident(handler: &Ident) -> Ident {
+    let name = handler
+        .to_string()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+
+    Ident::new(&name, handler.span())
+}
+
+/// Returns whether a type mentions a lifetime other than `'static`.
+fn has_lifetime(ty: &Type) -> bool {
+    struct Find(bool);
+
+    impl VisitMut for Find {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            self.0 |= lifetime.ident != "static";
+        }
+    }
+
+    let mut find = Find(false);
+    find.visit_type_mut(&mut ty.clone());
+    find.0
+}
+
+/// Implementation of the pino_processor macro
+pub fn pino_processor_impl(args: Vec<Meta>, mut input: ItemImpl) -> Result<TokenStream> {
+    let scheme = DiscriminatorScheme::from_args(&args)?;
+
+    if input.trait_.is_some() || !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.self_ty,
+            "#[pino_processor] requires an inherent impl of a non-generic type",
+        ));
+    }
+
+    let processor = match &*input.self_ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| Error::new_spanned(&input.self_ty, "expected a type name"))?,
+        ty => return Err(Error::new_spanned(ty, "expected a type name")),
+    };
+    let enum_ident = format_ident!("{}Instruction", processor);
+    let hooks = Hooks::take(&mut input.attrs)?;
+
+    // Extract function handlers
+    let mut handlers = Vec::new();
+    let mut fallback = None;
+    for item in input.items.iter_mut() {
+        if let ImplItem::Fn(func) = item {
+            if let Some(position) = func
+                .attrs
+                .iter()
+                .position(|attr| attr.path().is_ident("fallback"))
+            {
+                let attr = func.attrs.remove(position);
+                attr.meta.require_path_only()?;
+                if fallback.is_some() {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "a processor can only have one #[fallback] function",
+                    ));
+                }
+                fallback = Some(func.sig.ident.clone());
+                continue;
+            }
+
+            let Some(position) = func
+                .attrs
+                .iter()
+                .position(|attr| attr.path().is_ident("instruction"))
+            else {
+                continue;
+            };
+            let attr = func.attrs.remove(position);
+            let discriminator = DiscriminatorAttr::from_attr(&attr)?.resolve(
+                scheme,
+                handlers.len(),
+                &func.sig.ident,
+            )?;
+            let handler_hooks = hooks.around(Hooks::take(&mut func.attrs)?);
+            handlers.push(Handler::from_sig(&func.sig, discriminator, handler_hooks)?);
+        }
+    }
+
+    check_unique(
+        &handlers
+            .iter()
+            .map(|handler| (&handler.ident, handler.discriminator.as_slice()))
+            .collect::<Vec<_>>(),
+    )?;
+
+    let variants = handlers
+        .iter()
+        .map(|handler| variant_ident(&handler.ident))
+        .collect::<Vec<_>>();
+    let variant_docs = handlers
+        .iter()
+        .map(|handler| format!(" Routed to `{}::{}`.", processor, handler.ident));
+    let enum_doc = format!(" Instructions routed by [`{}`].", processor);
+
+    // Variants borrow their arguments from the instruction data. The enum
+    // only takes a lifetime if some argument has one, since it would
+    // otherwise be unused.
+    let lifetime = Lifetime::new("'a", Span::call_site());
+    let fields = handlers
+        .iter()
+        .map(|handler| handler.arg_types(&lifetime))
+        .collect::<Vec<_>>();
+    let enum_generics = if fields.iter().flatten().any(has_lifetime) {
+        quote! { <#lifetime> }
+    } else {
+        TokenStream::new()
+    };
+    let enum_variants = variants.iter().zip(&fields).map(|(variant, fields)| {
+        if fields.is_empty() {
+            quote! { #variant }
+        } else {
+            quote! { #variant(#(#fields),*) }
+        }
+    });
+
+    // Unmatched instruction data decodes to a variant of its own, whose
+    // argument bytes are the whole instruction data.
+    let fallback_variant = fallback.as_ref().map(|fallback| {
+        let variant = variant_ident(fallback);
+        let doc = format!(
+            " Instruction data matching no discriminator, routed to `{}::{}`.",
+            processor, fallback
+        );
+        quote! {
+            #[doc = #doc]
+            #variant,
+        }
+    });
+    let (fallback_decode, fallback_discriminator, fallback_process) = match &fallback {
+        Some(fallback) => {
+            let variant = variant_ident(fallback);
+            (
+                quote! { _ => Ok((Self::#variant, instruction_data)), },
+                quote! { Self::#variant => &[], },
+                quote! { #enum_ident::#variant => Self::#fallback(program_id, accounts, data), },
+            )
+        }
+        None => (
+            quote! { _ => Err(::pino_core::error::PinoError::InstructionFallbackNotFound), },
+            TokenStream::new(),
+            TokenStream::new(),
+        ),
+    };
+    let variant_patterns = handlers
+        .iter()
+        .zip(&variants)
+        .map(|(handler, variant)| {
+            let names = handler.arg_idents();
+            if names.is_empty() {
+                quote! { #variant }
+            } else {
+                quote! { #variant(#(#names),*) }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let decode_arms = handlers.iter().zip(&variant_patterns).map(|(handler, pattern)| {
+        let bytes = &handler.discriminator;
+        let decode_args = handler.decode_args(&lifetime);
+        quote! {
+            [#(#bytes,)* data @ ..] => {
+                #decode_args
+                Ok((Self::#pattern, data))
+            }
+        }
+    });
+    let discriminator_arms = handlers.iter().zip(&variants).map(|(handler, variant)| {
+        let bytes = &handler.discriminator;
+        quote! {
+            Self::#variant { .. } => &[#(#bytes),*],
+        }
+    });
+    let process_arms = handlers.iter().zip(&variant_patterns).map(|(handler, pattern)| {
+        let ident = &handler.ident;
+        let dispatch = handler.dispatch(quote! { Self::#ident });
+        quote! {
+            #enum_ident::#pattern => { #dispatch }
+        }
+    });
+
+    let self_ty = &input.self_ty;
+    let idl_instructions = handlers.iter().map(Handler::idl_instruction);
+    let idl = crate::idl::print_test(
+        &format!("instructions_{}", processor),
+        quote! { #(#idl_instructions)* },
+    );
+
+    Ok(quote! {
+        #input
+
+        #[doc = #enum_doc]
+        pub enum #enum_ident #enum_generics {
+            #(
+                #[doc = #variant_docs]
+                #enum_variants,
+            )*
+            #fallback_variant
+        }
+
+        impl<#lifetime> #enum_ident #enum_generics {
+            /// Splits instruction data into the instruction it selects, with
+            /// its arguments decoded, and the bytes of those arguments.
+            pub fn decode(
+                instruction_data: &#lifetime [u8],
+            ) -> ::core::result::Result<(Self, &#lifetime [u8]), ::pino_core::error::PinoError> {
+                match instruction_data {
+                    #(#decode_arms)*
+                    #fallback_decode
+                }
+            }
+
+            /// Returns the discriminator that selects the instruction, which
+            /// is empty for the fallback.
+            pub const fn discriminator(&self) -> &'static [u8] {
+                match self {
+                    #(#discriminator_arms)*
+                    #fallback_discriminator
+                }
+            }
+        }
+
+        impl ::pino_core::entrypoint::ProgramInstruction for #self_ty {
+            type Instruction<#lifetime> = #enum_ident #enum_generics;
+
+            fn decode(
+                instruction_data: &[u8],
+            ) -> ::core::result::Result<(Self::Instruction<'_>, &[u8]), ::pino_core::error::PinoError> {
+                #enum_ident::decode(instruction_data)
+            }
+
+            #[allow(unused_variables)]
+            fn process<#lifetime>(
+                instruction: Self::Instruction<#lifetime>,
+                program_id: &#lifetime ::pino_core::Pubkey,
+                accounts: ::pino_core::context::RemainingAccounts<#lifetime>,
+                data: &#lifetime [u8],
+            ) -> ::pino_core::ProgramResult {
+                match instruction {
+                    #(#process_arms)*
+                    #fallback_process
+                }
+            }
+        }
+
+        #idl
+    })
+}
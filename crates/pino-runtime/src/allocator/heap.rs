@@ -0,0 +1,398 @@
+//! Global allocators over the program heap.
+//!
+//! The allocators in the sibling modules keep their bookkeeping in Rust
+//! values, which a program cannot store in a writable `static`. The types
+//! here implement `GlobalAlloc` instead, keeping their state in a header at
+//! the start of the heap region. The runtime zeroes the heap before every
+//! instruction, so an all-zero header means nothing was allocated yet.
+//!
+//! `pino_entrypoint!` installs one of them with the `allocator` key.
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::size_of;
+use core::ptr::null_mut;
+
+/// Address of the program heap.
+pub const HEAP_START_ADDRESS: usize = 0x300000000;
+
+/// Heap size available without a `RequestHeapFrame` instruction.
+pub const DEFAULT_HEAP_SIZE: usize = 32 * 1024;
+
+/// Largest heap a transaction can request.
+pub const MAX_HEAP_SIZE: usize = 256 * 1024;
+
+/// Rounds `address` up to `align`, which must be a power of two.
+#[inline(always)]
+fn align_up(address: usize, align: usize) -> Option<usize> {
+    Some(address.checked_add(align - 1)? & !(align - 1))
+}
+
+/// Allocates by moving a cursor forward; memory is never reused.
+pub struct BumpHeap {
+    start: usize,
+    len: usize,
+}
+
+impl BumpHeap {
+    /// Creates a bump allocator over the first `len` bytes of the heap.
+    pub const fn new(len: usize) -> Self {
+        Self::at(HEAP_START_ADDRESS, len)
+    }
+
+    /// Creates a bump allocator over `len` zeroed bytes at `start`.
+    const fn at(start: usize, len: usize) -> Self {
+        Self { start, len }
+    }
+
+    /// Returns the cursor, stored in the first word of the heap.
+    #[inline(always)]
+    fn cursor(&self) -> *mut usize {
+        self.start as *mut usize
+    }
+}
+
+unsafe impl GlobalAlloc for BumpHeap {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let cursor = self.cursor();
+        let position = match *cursor {
+            0 => self.start + size_of::<usize>(),
+            position => position,
+        };
+
+        let Some(address) = align_up(position, layout.align()) else {
+            return null_mut();
+        };
+        match address.checked_add(layout.size()) {
+            Some(end) if end <= self.start + self.len => {
+                *cursor = end;
+                address as *mut u8
+            }
+            _ => null_mut(),
+        }
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        // Bump allocations are released when the instruction ends.
+    }
+}
+
+/// Allocates like [`BumpHeap`], but releases the most recent allocation when
+/// it is freed, so LIFO usage keeps reusing the same memory.
+pub struct StackHeap {
+    bump: BumpHeap,
+}
+
+impl StackHeap {
+    /// Creates a stack allocator over the first `len` bytes of the heap.
+    pub const fn new(len: usize) -> Self {
+        Self::at(HEAP_START_ADDRESS, len)
+    }
+
+    /// Creates a stack allocator over `len` zeroed bytes at `start`.
+    const fn at(start: usize, len: usize) -> Self {
+        Self { bump: BumpHeap::at(start, len) }
+    }
+}
+
+unsafe impl GlobalAlloc for StackHeap {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.bump.alloc(layout)
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let cursor = self.bump.cursor();
+        if ptr as usize + layout.size() == *cursor {
+            *cursor = ptr as usize;
+        }
+    }
+}
+
+/// Splits the heap into fixed-size regions and fills them in turn.
+///
+/// An allocation that does not fit in what is left of the current region
+/// starts the next one, so large and small allocations are kept apart.
+/// Allocations larger than a region fail.
+pub struct RegionHeap {
+    bump: BumpHeap,
+    region_size: usize,
+}
+
+impl RegionHeap {
+    /// Creates a region allocator over the first `len` bytes of the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `region_size` is zero, at compile time when the allocator
+    /// is a `static`.
+    pub const fn new(len: usize, region_size: usize) -> Self {
+        Self::at(HEAP_START_ADDRESS, len, region_size)
+    }
+
+    /// Creates a region allocator over `len` zeroed bytes at `start`.
+    const fn at(start: usize, len: usize, region_size: usize) -> Self {
+        assert!(region_size > 0, "the region size of a `RegionHeap` must not be zero");
+        Self {
+            bump: BumpHeap::at(start, len),
+            region_size,
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for RegionHeap {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() > self.region_size {
+            return null_mut();
+        }
+
+        let cursor = self.bump.cursor();
+        let position = match *cursor {
+            0 => self.bump.start + size_of::<usize>(),
+            position => position,
+        };
+        let Some(address) = align_up(position, layout.align()) else {
+            return null_mut();
+        };
+
+        // Regions are counted from the start of the heap, header included.
+        let offset = address - self.bump.start;
+        let region_end = (offset / self.region_size + 1) * self.region_size;
+        if offset + layout.size() > region_end {
+            let Some(next) = align_up(self.bump.start + region_end, layout.align()) else {
+                return null_mut();
+            };
+            *cursor = next;
+        }
+
+        self.bump.alloc(layout)
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        // Regions are released when the instruction ends.
+    }
+}
+
+/// Hands out blocks of a single size and reuses freed blocks.
+///
+/// Allocations larger than a block, or aligned more strictly than the block
+/// size allows, fail.
+pub struct PoolHeap {
+    bump: BumpHeap,
+    block_size: usize,
+}
+
+impl PoolHeap {
+    /// Creates a pool allocator over the first `len` bytes of the heap.
+    ///
+    /// Block sizes are rounded up to a whole number of words so that free
+    /// blocks can be linked.
+    pub const fn new(len: usize, block_size: usize) -> Self {
+        Self::at(HEAP_START_ADDRESS, len, block_size)
+    }
+
+    /// Creates a pool allocator over `len` zeroed bytes at `start`.
+    const fn at(start: usize, len: usize, block_size: usize) -> Self {
+        let word = size_of::<usize>();
+        let block_size = if block_size == 0 {
+            word
+        } else {
+            (block_size + word - 1) / word * word
+        };
+        Self {
+            // The free list head takes the first word of the header and the
+            // cursor the second.
+            bump: BumpHeap::at(start + word, len - word),
+            block_size,
+        }
+    }
+
+    /// Returns the head of the free list, stored in the first word of the
+    /// heap.
+    #[inline(always)]
+    fn free_list(&self) -> *mut usize {
+        (self.bump.start - size_of::<usize>()) as *mut usize
+    }
+
+    /// Returns the alignment every block satisfies.
+    #[inline(always)]
+    fn block_align(&self) -> usize {
+        1 << self.block_size.trailing_zeros().min(12)
+    }
+}
+
+unsafe impl GlobalAlloc for PoolHeap {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() > self.block_size || layout.align() > self.block_align() {
+            return null_mut();
+        }
+
+        let free_list = self.free_list();
+        if *free_list != 0 {
+            let block = *free_list;
+            *free_list = *(block as *const usize);
+            return block as *mut u8;
+        }
+
+        self.bump.alloc(
+            Layout::from_size_align_unchecked(self.block_size, self.block_align()),
+        )
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+        let free_list = self.free_list();
+        *(ptr as *mut usize) = *free_list;
+        *free_list = ptr as usize;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORD: usize = size_of::<usize>();
+
+    /// A zeroed stand-in for the program heap.
+    #[repr(C, align(64))]
+    struct Heap([u8; 256]);
+
+    impl Heap {
+        fn new() -> Self {
+            Self([0; 256])
+        }
+
+        fn start(&mut self) -> usize {
+            self.0.as_mut_ptr() as usize
+        }
+    }
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    #[test]
+    fn bump_aligns_allocations() {
+        let mut heap = Heap::new();
+        let start = heap.start();
+        let allocator = BumpHeap::at(start, 256);
+
+        unsafe {
+            let byte = allocator.alloc(layout(1, 1)) as usize;
+            assert_eq!(byte, start + WORD);
+
+            let aligned = allocator.alloc(layout(8, 16)) as usize;
+            assert_eq!(aligned % 16, 0);
+            assert_eq!(aligned, start + 16);
+        }
+    }
+
+    #[test]
+    fn bump_returns_null_when_exhausted() {
+        let mut heap = Heap::new();
+        let allocator = BumpHeap::at(heap.start(), 64);
+
+        unsafe {
+            assert!(allocator.alloc(layout(64, 1)).is_null());
+            assert!(!allocator.alloc(layout(64 - WORD, 1)).is_null());
+            assert!(allocator.alloc(layout(1, 1)).is_null());
+        }
+    }
+
+    #[test]
+    fn stack_reuses_the_last_allocation() {
+        let mut heap = Heap::new();
+        let allocator = StackHeap::at(heap.start(), 256);
+
+        unsafe {
+            let first = allocator.alloc(layout(16, 8));
+            let second = allocator.alloc(layout(16, 8));
+            allocator.dealloc(second, layout(16, 8));
+            assert_eq!(allocator.alloc(layout(16, 8)), second);
+
+            // Only the most recent allocation is released.
+            allocator.dealloc(first, layout(16, 8));
+            assert_ne!(allocator.alloc(layout(16, 8)), first);
+        }
+    }
+
+    #[test]
+    fn region_rolls_over_to_the_next_region() {
+        let mut heap = Heap::new();
+        let start = heap.start();
+        let allocator = RegionHeap::at(start, 256, 32);
+
+        unsafe {
+            assert_eq!(allocator.alloc(layout(16, 8)) as usize, start + WORD);
+            assert_eq!(allocator.alloc(layout(16, 8)) as usize, start + 32);
+            assert_eq!(allocator.alloc(layout(8, 8)) as usize, start + 48);
+            assert!(allocator.alloc(layout(33, 1)).is_null());
+        }
+    }
+
+    #[test]
+    fn region_returns_null_when_exhausted() {
+        let mut heap = Heap::new();
+        let allocator = RegionHeap::at(heap.start(), 64, 32);
+
+        unsafe {
+            assert!(!allocator.alloc(layout(16, 8)).is_null());
+            assert!(!allocator.alloc(layout(32, 8)).is_null());
+            assert!(allocator.alloc(layout(1, 1)).is_null());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn region_rejects_zero_sized_regions() {
+        RegionHeap::at(0, 256, 0);
+    }
+
+    #[test]
+    fn pool_rounds_blocks_to_words() {
+        assert_eq!(PoolHeap::new(256, 0).block_size, WORD);
+        assert_eq!(PoolHeap::new(256, 1).block_size, WORD);
+        assert_eq!(PoolHeap::new(256, WORD + 1).block_size, 2 * WORD);
+        assert_eq!(PoolHeap::new(256, 2 * WORD).block_size, 2 * WORD);
+    }
+
+    #[test]
+    fn pool_reuses_freed_blocks() {
+        let mut heap = Heap::new();
+        let allocator = PoolHeap::at(heap.start(), 256, 12);
+
+        unsafe {
+            let first = allocator.alloc(layout(12, 4));
+            let second = allocator.alloc(layout(8, 8));
+            assert_ne!(first, second);
+
+            allocator.dealloc(second, layout(8, 8));
+            allocator.dealloc(first, layout(12, 4));
+            assert_eq!(allocator.alloc(layout(4, 4)), first);
+            assert_eq!(allocator.alloc(layout(4, 4)), second);
+
+            let third = allocator.alloc(layout(4, 4));
+            assert!(third != first && third != second);
+        }
+    }
+
+    #[test]
+    fn pool_returns_null_for_oversized_or_exhausted_requests() {
+        let mut heap = Heap::new();
+        let allocator = PoolHeap::at(heap.start(), 64, 16);
+
+        unsafe {
+            assert!(allocator.alloc(layout(17, 1)).is_null());
+            // Two header words leave room for three 16-byte blocks.
+            for _ in 0..3 {
+                assert!(!allocator.alloc(layout(16, 8)).is_null());
+            }
+            assert!(allocator.alloc(layout(16, 8)).is_null());
+        }
+    }
+}
@@ -9,6 +9,7 @@ use core::alloc::{GlobalAlloc, Layout};
 use core::ptr::NonNull;
 
 pub mod bump;
+pub mod heap;
 pub mod pool;
 pub mod region;
 pub mod stack;
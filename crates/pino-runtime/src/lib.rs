@@ -20,6 +20,7 @@ pub mod profiler;
 // Re-exports from allocator module
 pub use allocator::{
     bump::BumpAllocator,
+    heap::{BumpHeap, PoolHeap, RegionHeap, StackHeap},
     pool::PoolAllocator,
     region::RegionAllocator,
     stack::StackAllocator,